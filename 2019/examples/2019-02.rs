@@ -128,9 +128,11 @@
 // Find the input *noun* and *verb* that cause the program to produce the output `19690720`. *What
 // is `100 * noun + verb`?* (For example, if `noun=12` and `verb=2`, the answer would be `1202`.)
 
-use _2019::{ints_from_str, Program};
+use _2019::{ints_from_str, symbolic, Program};
 
-fn main() {
+use anyhow::Result;
+
+fn main() -> Result<()> {
     let input = include_str!("./input/2019-02.txt").trim();
     let int_codes = ints_from_str(input);
 
@@ -138,21 +140,16 @@ fn main() {
     memory[1] = 12;
     memory[2] = 2;
     let mut program = Program::new(memory);
-    program.run_no_io();
+    program.run_no_io()?;
     aoc_lib::set_part_1!(program.get_memory()[0]);
 
+    // the result is linear in the noun and verb, so it's solved rather than searched for
     let target = 19690720;
-    for n in 0..=99 {
-        for v in 0..=99 {
-            let mut memory = int_codes.clone();
-            memory[1] = n;
-            memory[2] = v;
-            let mut program = Program::new(memory);
-            program.run_no_io();
-            if program.get_memory()[0] == target {
-                aoc_lib::set_part_2!(100 * n + v);
-                break;
-            }
-        }
+    if let Some(solution) =
+        symbolic::solve_memory(&int_codes, &[(1, 0..=99), (2, 0..=99)], 0, target)
+    {
+        aoc_lib::set_part_2!(100 * solution[0] + solution[1]);
     }
+
+    Ok(())
 }
@@ -113,7 +113,7 @@ fn all_points(line: &str) -> HashMap<(isize, isize), usize> {
     line.split(',').for_each(|movement| unsafe {
         let count = movement.get_unchecked(1..).parse::<isize>().unwrap();
         for _ in 0..count {
-            match &movement.get_unchecked(0..1)[..] {
+            match movement.get_unchecked(0..1) {
                 "L" => x -= 1,
                 "R" => x += 1,
                 "U" => y -= 1,
@@ -131,7 +131,7 @@ fn all_points(line: &str) -> HashMap<(isize, isize), usize> {
 fn main() -> Result<()> {
     let input = include_str!("./input/2019-03.txt").trim();
     let (line_a, line_b) = aoc_lib::utils::string_split2("\n", input);
-    let (points_a, points_b) = (all_points(&line_a), all_points(&line_b));
+    let (points_a, points_b) = (all_points(line_a), all_points(line_b));
 
     let make_set = |map: &HashMap<(isize, isize), usize>| {
         map.keys().cloned().collect::<HashSet<(isize, isize)>>()
@@ -143,7 +143,6 @@ fn main() -> Result<()> {
 
     let smallest_from_center = intersections
         .clone()
-        .into_iter()
         .map(|(x, y)| x.abs() + y.abs())
         .min()
         .unwrap();
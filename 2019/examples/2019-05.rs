@@ -165,7 +165,7 @@ fn main() -> Result<()> {
     {
         let mut program = Program::new(int_codes.clone());
         let mut output = vec![];
-        if program.run(1, &mut output) == StopReason::Halt {
+        if program.run(1, &mut output)? == StopReason::Halt {
             aoc_lib::set_part_1!(output.last().unwrap());
         }
     }
@@ -173,7 +173,7 @@ fn main() -> Result<()> {
     {
         let mut program = Program::new(int_codes.clone());
         let mut output = vec![];
-        if program.run(5, &mut output) == StopReason::Halt {
+        if program.run(5, &mut output)? == StopReason::Halt {
             aoc_lib::set_part_1!(output.last().unwrap());
         }
     }
@@ -131,65 +131,37 @@
 // Try every combination of the new phase settings on the amplifier feedback loop. *What is the
 // highest signal that can be sent to the thrusters?*
 
-use _2019::{ints_from_str, Program, StopReason};
+use _2019::scheduler::{Pipeline, Ring, Scheduler, Topology};
+use _2019::{ints_from_str, Int, IntcodeError, Program};
 
 use anyhow::Result;
 use itertools::Itertools;
 
+/// Run the amplifiers with the given phase settings and return the signal sent to the thrusters
+fn signal(ints: &[Int], phases: Vec<Int>, topology: impl Topology) -> Result<Int, IntcodeError> {
+    let programs = phases.iter().map(|_| Program::new(ints.to_vec())).collect();
+    let mut amplifiers = Scheduler::new(programs, topology);
+    for (amplifier, phase) in phases.into_iter().enumerate() {
+        amplifiers.send(amplifier, Some(phase));
+    }
+    amplifiers.send(0, Some(0));
+    amplifiers.run()?;
+
+    Ok(amplifiers.last_output(amplifiers.len() - 1).unwrap())
+}
+
 fn main() -> Result<()> {
     let input = include_str!("./input/2019-07.txt").trim();
     let int_codes = ints_from_str(input);
 
-    let highest_signal = (0..5).permutations(5).fold(0, |result, mut combination| {
-        let mut a = Program::new(int_codes.clone());
-        let mut b = Program::new(int_codes.clone());
-        let mut c = Program::new(int_codes.clone());
-        let mut d = Program::new(int_codes.clone());
-        let mut e = Program::new(int_codes.clone());
-
-        let mut a_input = vec![combination.pop().unwrap(), 0];
-        let mut b_input = vec![combination.pop().unwrap()];
-        let mut c_input = vec![combination.pop().unwrap()];
-        let mut d_input = vec![combination.pop().unwrap()];
-        let mut e_input = vec![combination.pop().unwrap()];
-        let mut e_output = vec![];
-
-        a.run(&mut a_input, &mut b_input);
-        b.run(&mut b_input, &mut c_input);
-        c.run(&mut c_input, &mut d_input);
-        d.run(&mut d_input, &mut e_input);
-        e.run(&mut e_input, &mut e_output);
-
-        std::cmp::max(result, e_output[0])
-    });
+    let highest_signal = (0..5).permutations(5).try_fold(0, |result, phases| {
+        Ok::<_, IntcodeError>(result.max(signal(&int_codes, phases, Pipeline)?))
+    })?;
     aoc_lib::set_part_1!(highest_signal);
 
-    let highest_signal = (5..10).permutations(5).fold(0, |result, mut combination| {
-        let mut a_input = vec![combination.pop().unwrap(), 0];
-        let mut b_input = vec![combination.pop().unwrap()];
-        let mut c_input = vec![combination.pop().unwrap()];
-        let mut d_input = vec![combination.pop().unwrap()];
-        let mut e_input = vec![combination.pop().unwrap()];
-
-        let mut a = Program::new(int_codes.clone());
-        let mut b = Program::new(int_codes.clone());
-        let mut c = Program::new(int_codes.clone());
-        let mut d = Program::new(int_codes.clone());
-        let mut e = Program::new(int_codes.clone());
-
-        loop {
-            a.run(&mut a_input, &mut b_input);
-            b.run(&mut b_input, &mut c_input);
-            c.run(&mut c_input, &mut d_input);
-            d.run(&mut d_input, &mut e_input);
-            if let StopReason::Halt = e.run(&mut e_input, &mut a_input) {
-                break;
-            }
-        }
-
-        std::cmp::max(result, a_input[0])
-    });
-
+    let highest_signal = (5..10).permutations(5).try_fold(0, |result, phases| {
+        Ok::<_, IntcodeError>(result.max(signal(&int_codes, phases, Ring)?))
+    })?;
     aoc_lib::set_part_2!(highest_signal);
 
     Ok(())
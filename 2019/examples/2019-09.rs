@@ -80,6 +80,7 @@
 // Run the BOOST program in sensor boost mode. *What are the coordinates of the distress signal?*
 
 use anyhow::Result;
+use std::collections::VecDeque;
 
 use _2019::{ints_from_str, Program};
 
@@ -90,13 +91,13 @@ fn main() -> Result<()> {
     {
         let mut program = Program::new(ints.clone());
         let mut output = vec![];
-        program.run(&mut vec![1], &mut output);
+        program.run(VecDeque::from([1]), &mut output)?;
         aoc_lib::set_part_1!(output[0]);
     }
     {
         let mut program = Program::new(ints);
         let mut output = vec![];
-        program.run(&mut vec![2], &mut output);
+        program.run(VecDeque::from([2]), &mut output)?;
         aoc_lib::set_part_2!(output[0]);
     }
 
@@ -64,183 +64,232 @@
 // delivered by the NAT to the computer at address `0` twice in a row?*
 
 use anyhow::Result;
-use lazy_static::lazy_static;
-use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Condvar, Mutex};
 use std::thread::{self, JoinHandle};
-use std::time::Duration;
 
-use _2019::io::{IntRead, IntWrite};
-use _2019::{ints_from_str, Int, Program};
+use _2019::io::{channel, BlockingReader, ChannelWriter, IntRead, IntWrite};
+use _2019::scheduler::{self, Scheduler};
+use _2019::{ints_from_str, Int, IntcodeError, Program};
 
-lazy_static! {
-    static ref PART_1_COMPLETE: AtomicBool = AtomicBool::new(false);
-    static ref PART_2_SEEN: Mutex<HashSet<Int>> = Mutex::new(HashSet::new());
-}
-
-type NetAddress = usize;
+const NICS: usize = 50;
+const NAT_ADDRESS: usize = 255;
 
-/// Not Always Transmitting
-struct NAT {
-    pub addr: NetAddress,
-    packet: Option<(Int, Int)>,
+/// What the NAT needs to know to tell when the network is idle
+struct State {
+    /// Whether each NIC is blocked waiting for a packet
+    idle: Vec<bool>,
+    /// How many ints were sent to each NIC that it hasn't read yet
+    queued: Vec<usize>,
+    /// The last packet sent to the NAT
+    nat_packet: Option<(Int, Int)>,
+    /// The y of the first packet sent to the NAT
+    first_y: Option<Int>,
 }
 
-impl NAT {
-    pub fn new(addr: NetAddress) -> NAT {
-        NAT { addr, packet: None }
-    }
-
-    pub fn set_packet(&mut self, packet: (Int, Int)) {
-        self.packet = Some(packet);
-    }
-
-    pub fn check_idle(&self, network: &mut Network) {
-        if let Some((x, y)) = self.packet {
-            let is_idle = network
-                .iter()
-                .all(|(net_addr, buf)| *net_addr == self.addr || buf.is_empty());
-
-            // send resume packet
-            if is_idle {
-                {
-                    let mut set = PART_2_SEEN.lock().unwrap();
-                    if set.contains(&y) {
-                        aoc_lib::set_part_2!(y);
-                        std::process::exit(0);
-                    } else {
-                        set.insert(y);
-                    }
-                }
-
-                network.send_int(0, x);
-                network.send_int(0, y);
-            }
-        }
+impl State {
+    fn is_idle(&self) -> bool {
+        self.idle.iter().all(|idle| *idle) && self.queued.iter().all(|queued| *queued == 0)
     }
 }
 
 struct Network {
-    inner: HashMap<NetAddress, Vec<Int>>,
+    state: Mutex<State>,
+    /// Notified whenever a NIC goes idle
+    idle: Condvar,
+    /// Set once the answer is found, after which the NICs stop at their next read
+    done: AtomicBool,
+}
+
+/// A NIC's input, which reads -1 when there are no packets waiting. If there's still nothing the
+/// next time it reads, the NIC is idle and blocks until it's sent a packet.
+struct NicInput {
+    address: usize,
+    reader: BlockingReader,
+    network: Arc<Network>,
+    /// Whether its last read found no packet
+    polled: bool,
 }
 
-impl Network {
-    pub fn new() -> Network {
-        Network {
-            inner: HashMap::new(),
+impl IntRead for NicInput {
+    fn int_read(&mut self) -> Option<Int> {
+        if self.network.done.load(Ordering::Relaxed) {
+            return None;
         }
-    }
 
-    pub fn iter(&self) -> impl Iterator<Item = (&usize, &Vec<Int>)> {
-        self.inner.iter()
-    }
+        let mut state = self.network.state.lock().unwrap();
+        if state.queued[self.address] == 0 {
+            if !self.polled {
+                self.polled = true;
+                return Some(-1);
+            }
 
-    pub fn receive_int(&mut self, net_addr: NetAddress) -> Int {
-        let queue = self.inner.entry(net_addr).or_insert(vec![]);
-        match queue.len() {
-            0 => -1,
-            _ => queue.remove(0),
-        }
-    }
+            state.idle[self.address] = true;
+            self.network.idle.notify_one();
+            drop(state);
 
-    pub fn receive_packet(&mut self, net_addr: NetAddress) -> Option<(Int, Int)> {
-        let queue = self.inner.entry(net_addr).or_insert(vec![]);
-        match queue.len() {
-            0 | 1 => None,
-            _ => Some((queue.remove(0), queue.remove(0))),
+            // only a packet from another NIC or the NAT wakes it up again
+            let value = self.reader.int_read()?;
+            if self.network.done.load(Ordering::Relaxed) {
+                return None;
+            }
+            state = self.network.state.lock().unwrap();
+            state.idle[self.address] = false;
+            state.queued[self.address] -= 1;
+            self.polled = false;
+            return Some(value);
         }
-    }
 
-    pub fn send_int(&mut self, net_addr: NetAddress, int: Int) {
-        let queue = self.inner.entry(net_addr).or_insert(vec![]);
-        queue.push(int);
+        state.queued[self.address] -= 1;
+        self.polled = false;
+        drop(state);
+        self.reader.int_read()
     }
 }
 
-#[derive(Clone)]
-struct Port {
-    addr: usize,
+/// A NIC's output, which sends each packet once it has its address, x and y
+struct NicOutput {
     outgoing: Vec<Int>,
-    network: Arc<Mutex<Network>>,
+    nics: Vec<ChannelWriter>,
+    network: Arc<Network>,
 }
 
-impl Port {
-    pub fn new(network: Arc<Mutex<Network>>, addr: NetAddress) -> Port {
-        Port {
-            addr,
-            outgoing: vec![],
-            network,
-        }
-    }
-}
-
-impl IntRead for Port {
-    fn int_read(&mut self) -> Option<Int> {
-        let mut inner = self.network.lock().unwrap();
-        Some(inner.receive_int(self.addr))
-    }
-}
-
-impl IntWrite for Port {
+impl IntWrite for NicOutput {
     fn int_write(&mut self, int: Int) {
         self.outgoing.push(int);
+        if self.outgoing.len() < 3 {
+            return;
+        }
 
-        // if we've got a full packet, then send it through to the destination program
-        if self.outgoing.len() == 3 {
-            let y = self.outgoing.pop().unwrap();
-            let x = self.outgoing.pop().unwrap();
-            let dest_net_addr = self.outgoing.pop().unwrap() as usize;
-
-            if !PART_1_COMPLETE.load(Ordering::SeqCst) && dest_net_addr == 255 {
-                aoc_lib::set_part_1!(y);
-                PART_1_COMPLETE.store(true, Ordering::SeqCst);
-            }
+        let (address, x, y) = (
+            self.outgoing[0] as usize,
+            self.outgoing[1],
+            self.outgoing[2],
+        );
+        self.outgoing.clear();
 
-            let mut inner = self.network.lock().unwrap();
-            inner.send_int(dest_net_addr, x);
-            inner.send_int(dest_net_addr, y);
+        let mut state = self.network.state.lock().unwrap();
+        if address == NAT_ADDRESS {
+            state.first_y = state.first_y.or(Some(y));
+            state.nat_packet = Some((x, y));
+        } else {
+            state.queued[address] += 2;
+            self.nics[address].int_write(x);
+            self.nics[address].int_write(y);
         }
     }
 }
 
-fn main() -> Result<()> {
-    let input = include_str!("./input/2019-23.txt").trim();
-    let ints = ints_from_str(input);
+/// Run every NIC on its own thread, connected by channels
+fn threaded(ints: &[Int]) -> Result<(Int, Int), IntcodeError> {
+    let network = Arc::new(Network {
+        state: Mutex::new(State {
+            idle: vec![false; NICS],
+            // each NIC's first input is its address
+            queued: vec![1; NICS],
+            nat_packet: None,
+            first_y: None,
+        }),
+        idle: Condvar::new(),
+        done: AtomicBool::new(false),
+    });
+
+    let (mut nics, readers): (Vec<_>, Vec<_>) = (0..NICS).map(|_| channel()).unzip();
+    for (address, nic) in nics.iter_mut().enumerate() {
+        nic.int_write(address as Int);
+    }
 
-    let net = Arc::new(Mutex::new(Network::new()));
-    let mut threads = (0..50)
-        .map(|net_addr| {
-            let net = net.clone();
-            let ints = ints.clone();
+    let threads = readers
+        .into_iter()
+        .enumerate()
+        .map(|(address, reader)| {
+            let ints = ints.to_vec();
+            let input = NicInput {
+                address,
+                reader,
+                network: network.clone(),
+                polled: false,
+            };
+            let output = NicOutput {
+                outgoing: vec![],
+                nics: nics.clone(),
+                network: network.clone(),
+            };
             thread::spawn(move || {
-                let port = Port::new(net, net_addr);
-                let mut program = Program::new(ints);
-                // run the program once to give it its network address
-                program.run(&mut vec![net_addr as Int], port.clone());
-                // now connect it to the network and run it again
-                program.run(port.clone(), port);
+                Program::new(ints).run(input, output).unwrap();
             })
         })
         .collect::<Vec<JoinHandle<()>>>();
 
-    // setup the NAT
-    let mut nat = NAT::new(255);
-    threads.push(thread::spawn(move || loop {
-        thread::sleep(Duration::from_millis(100));
-        {
-            let mut net = net.lock().unwrap();
-            while let Some(packet) = net.receive_packet(nat.addr) {
-                nat.set_packet(packet);
-            }
-            nat.check_idle(&mut net);
+    // the NAT: wait for the network to go idle, then wake it up with the last packet it was sent
+    let mut last_y = None;
+    let answer = loop {
+        let mut state = network
+            .idle
+            .wait_while(network.state.lock().unwrap(), |state| {
+                state.nat_packet.is_none() || !state.is_idle()
+            })
+            .unwrap();
+
+        let (x, y) = state.nat_packet.unwrap();
+        if last_y == Some(y) {
+            break (state.first_y.unwrap(), y);
         }
-    }));
+        last_y = Some(y);
+
+        state.queued[0] += 2;
+        nics[0].int_write(x);
+        nics[0].int_write(y);
+    };
 
-    // wait for all threads (indefinitely)
+    // wake up every NIC so it sees that the network is done
+    network.done.store(true, Ordering::Relaxed);
+    for nic in &mut nics {
+        nic.int_write(-1);
+    }
     for t in threads {
         let _ = t.join();
     }
 
+    Ok(answer)
+}
+
+/// Run every NIC on a single thread, taking turns
+fn scheduled(ints: &[Int]) -> Result<(Int, Int), IntcodeError> {
+    let programs = (0..NICS).map(|_| Program::new(ints.to_vec())).collect();
+    let mut network = Scheduler::new(programs, scheduler::Network::new(3)).with_default_input(-1);
+    for address in 0..NICS {
+        network.send(address, Some(address as Int));
+    }
+
+    let (mut first_y, mut nat_packet, mut last_y) = (None, None, None);
+    loop {
+        // the NICs never halt, so this runs until the network is idle
+        network.run()?;
+        for (_, packet) in network.take_output() {
+            first_y = first_y.or(Some(packet[2]));
+            nat_packet = Some((packet[1], packet[2]));
+        }
+
+        let (x, y) = nat_packet.unwrap();
+        if last_y == Some(y) {
+            return Ok((first_y.unwrap(), y));
+        }
+        last_y = Some(y);
+        network.send(0, [x, y]);
+    }
+}
+
+fn main() -> Result<()> {
+    let input = include_str!("./input/2019-23.txt").trim();
+    let ints = ints_from_str(input);
+
+    let (part_1, part_2) = aoc_lib::variants! {
+        "threads" => threaded(&ints),
+        "scheduler" => scheduled(&ints),
+    }?;
+    aoc_lib::set_part_1!(part_1);
+    aoc_lib::set_part_2!(part_2);
+
     Ok(())
 }
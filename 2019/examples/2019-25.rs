@@ -57,98 +57,78 @@
 //
 // The navigation console beeps again.
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use aoc_lib::utils::prompt_from_stdin;
-use std::io::Write;
 
-use _2019::io::{IntRead, IntWrite};
-use _2019::{ints_from_str, Int, Program, StopReason};
+use _2019::adventure::Autopilot;
+use _2019::io::{AsciiInput, AsciiOutput, IntWrite};
+use _2019::{ints_from_str, Int, Program, Snapshot, StopReason};
 
-struct Bits(Vec<u8>);
-
-impl IntRead for &mut Bits {
-    fn int_read(&mut self) -> Option<Int> {
-        match self.0.len() {
-            0 => None,
-            _ => Some(self.0.remove(0) as Int),
-        }
-    }
-}
-
-impl IntWrite for &mut Bits {
-    fn int_write(&mut self, int: Int) {
-        self.0.push(int as u8);
-    }
-}
-
-fn main() -> Result<()> {
-    let input = include_str!("./input/2019-25.txt").trim();
-    let ints = ints_from_str(input);
-
-    let mut droid = Program::new(ints);
-    let mut input = Bits(vec![]);
-    let mut output = Bits(vec![]);
+/// Explore the ship by hand, typing the droid's commands
+fn interactive(mut droid: Program) -> Result<()> {
+    let mut input = AsciiInput::new();
+    let mut output = AsciiOutput::new();
+    // the droid's last message, which is shown again after `!save` and `!load`
+    let mut text = String::new();
     loop {
-        match droid.run(&mut input, &mut output) {
+        match droid.run(&mut input, &mut output)? {
             StopReason::Halt => {
-                println!("{}", String::from_utf8_lossy(&output.0));
+                println!("{}", output.take_text());
                 break;
             }
             StopReason::WaitingForInput => {
-                let output = output.0.drain(..).collect::<Vec<_>>();
-                let answer = prompt_from_stdin(Some(&format!(
-                    "{} ",
-                    String::from_utf8_lossy(&output).trim_end()
-                )))?;
-                input.0.write_all(format!("{}\n", answer).as_bytes())?;
+                text.push_str(&output.take_text());
+                let answer = prompt_from_stdin(Some(&format!("{} ", text.trim_end())))?;
+
+                // `!save <path>` and `!load <path>` save and resume the exploration of the ship
+                if let Some(path) = answer.strip_prefix("!save ") {
+                    let pending = text.bytes().map(Int::from);
+                    if let Err(e) = droid.snapshot().with_io(vec![], pending).save(path.trim()) {
+                        println!("{}", e);
+                    }
+                } else if let Some(path) = answer.strip_prefix("!load ") {
+                    match Snapshot::load(path.trim()) {
+                        Ok(snapshot) => {
+                            droid.restore(&snapshot);
+                            input.clear();
+                            input.push_ints(snapshot.input.iter().copied());
+                            let mut saved = AsciiOutput::new();
+                            for i in &snapshot.output {
+                                saved.int_write(*i);
+                            }
+                            text = saved.take_text();
+                        }
+                        Err(e) => println!("{}", e),
+                    }
+                } else {
+                    input.push_line(&answer);
+                    text.clear();
+                }
                 println!();
             }
+            reason => return Err(anyhow!("the droid stopped unexpectedly: {:?}", reason)),
         }
     }
 
-    // CORRECT: brochure + ice cream + asterisk + bottle + heater
-    // BY WEIGHT:
-    //   spool, brochure
-    //   space heater, asterisk, bottle
-    //   shell, jam
-    //   ice cream
-    //
-    // ice cream + all small == too heavy
-    // ice cream + all small - asterisk == too heavy
-    // ice cream + all small - asterisk - space heater == too light
-    // ice cream + all small - asterisk - bottle == too light
-    // ice cream + all small - asterisk - spool == too heavy
-    // ice cream + all small - asterisk - brochure == too heavy
-    //
-    // shell + space heater == too light
-    // shell + jam == too light
-    // shell + brochure == too light
-    // shell + ice cream == too heavy
-    //
-    // ice cream + brochure == too light
-    // ice cream + space heater == too light
-    // ice cream + spool == too light
-    // ice cream + asterisk == too light
-    // ice cream + bottle == too light
-    // ice cream + jam == too heavy
-    // ice cream + shell == too heavy
-    //
-    // space heater + shell == too light
-    // space heater + ice cream == too light
-    // space heater + brochure == too light
-    // space heater + jam == too light
-    //
-    // jam + space heater == too light
-    // jam + shell == too light
-    // jam + brochure == too light
-    // jam + ice cream == too heavy
-    //
-    // brochure + space heater == too light
-    // brochure + shell == too light
-    // brochure + jam == too light
-    // brochure + ice cream == too light
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let input = include_str!("./input/2019-25.txt").trim();
+    let droid = Program::new(ints_from_str(input));
+
+    // `2019-25 --interactive` to play the game instead
+    if std::env::args().any(|arg| arg == "--interactive") {
+        return interactive(droid);
+    }
 
-    aoc_lib::set_part_1!(2105377);
+    let solution = Autopilot::new(droid).solve()?;
+    println!(
+        "carrying {} after {} attempts",
+        solution.items.join(", "),
+        solution.attempts
+    );
+    aoc_lib::set_part_1!(solution.password);
 
     // TODO: can't do part 2 yet, need to complete all the other puzzles
     // aoc_lib::set_part_2!(0);
@@ -0,0 +1,349 @@
+//! An autopilot for the droid's text adventure (2019-25). It maps the ship, picks up every item
+//! that's safe to carry, then works out which items get it past the pressure-sensitive floor.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, Result};
+
+use super::io::{AsciiInput, AsciiOutput};
+use super::program::{Program, StopReason};
+
+/// Items that end the game or leave the droid stuck, see `Autopilot::with_deadly`
+pub const DEADLY_ITEMS: [&str; 5] = [
+    "escape pod",
+    "giant electromagnet",
+    "infinite loop",
+    "molten lava",
+    "photons",
+];
+
+/// How many instructions a single command may take before the droid is assumed to be stuck
+const BUDGET: u64 = 10_000_000;
+
+/// The most items the droid will try the combinations of, there are `2^items` of them
+const MAX_ITEMS: usize = 20;
+
+/// What the floor says when the droid is carrying too little
+const TOO_LIGHT: &str = "heavier than the detected value";
+/// What the floor says when the droid is carrying too much
+const TOO_HEAVY: &str = "lighter than the detected value";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Room {
+    pub name: String,
+    pub description: String,
+    pub doors: Vec<String>,
+    pub items: Vec<String>,
+}
+
+impl Room {
+    /// Parse the last room described in the droid's output. Some commands describe two rooms, e.g.
+    /// stepping onto the floor and being ejected back to the checkpoint.
+    pub fn parse(text: &str) -> Option<Room> {
+        let start = text.rfind("== ")?;
+        let mut lines = text[start..].lines().map(str::trim);
+        let name = lines.next()?.trim_matches(|c| c == '=' || c == ' ');
+
+        let mut room = Room {
+            name: name.to_string(),
+            description: String::new(),
+            doors: vec![],
+            items: vec![],
+        };
+        let mut list = None;
+        for line in lines {
+            match line {
+                "" => list = None,
+                "Doors here lead:" => list = Some(&mut room.doors),
+                "Items here:" => list = Some(&mut room.items),
+                _ => match (line.strip_prefix("- "), list.as_mut()) {
+                    (Some(entry), Some(list)) => list.push(entry.to_string()),
+                    _ if room.description.is_empty() => room.description = line.to_string(),
+                    _ => {}
+                },
+            }
+        }
+
+        Some(room)
+    }
+}
+
+fn opposite(door: &str) -> Result<&'static str> {
+    match door {
+        "north" => Ok("south"),
+        "south" => Ok("north"),
+        "east" => Ok("west"),
+        "west" => Ok("east"),
+        _ => Err(anyhow!("unknown direction '{}'", door)),
+    }
+}
+
+/// What the droid said in response to a command
+enum Reply {
+    Text(String),
+    /// The game ended, e.g. because the droid got past the floor (or picked up something deadly)
+    Halted(String),
+    /// The droid ran out of budget without asking for another command
+    Stuck,
+}
+
+/// How the droid got past the floor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    /// The password for the main airlock
+    pub password: String,
+    /// The items the droid carried onto the floor
+    pub items: Vec<String>,
+    /// How many times the droid stepped onto the floor
+    pub attempts: usize,
+}
+
+pub struct Autopilot {
+    droid: Program,
+    input: AsciiInput,
+    output: AsciiOutput,
+    deadly: HashSet<String>,
+    /// Every room that's been visited by name, along with where each of its doors lead
+    ship: HashMap<String, (Room, HashMap<String, String>)>,
+    inventory: Vec<String>,
+    /// The doors from the start to the security checkpoint
+    checkpoint: Option<Vec<String>>,
+    /// The door from the checkpoint onto the floor
+    floor: Option<String>,
+}
+
+impl Autopilot {
+    pub fn new(droid: Program) -> Autopilot {
+        Autopilot {
+            droid,
+            input: AsciiInput::new(),
+            output: AsciiOutput::new(),
+            deadly: DEADLY_ITEMS.iter().map(|item| item.to_string()).collect(),
+            ship: HashMap::new(),
+            inventory: vec![],
+            checkpoint: None,
+            floor: None,
+        }
+    }
+
+    /// Never pick up these items. Items that end the game or stop the droid from answering are
+    /// detected anyway, but an item that leaves the droid unable to move isn't.
+    pub fn with_deadly(mut self, items: impl IntoIterator<Item = impl Into<String>>) -> Autopilot {
+        self.deadly = items.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// The rooms that have been mapped so far, along with where each of their doors lead
+    pub fn ship(&self) -> &HashMap<String, (Room, HashMap<String, String>)> {
+        &self.ship
+    }
+
+    pub fn inventory(&self) -> &[String] {
+        &self.inventory
+    }
+
+    fn run(&mut self) -> Result<Reply> {
+        let reason = self
+            .droid
+            .run_with_budget(&mut self.input, &mut self.output, BUDGET)?;
+        let text = self.output.take_text();
+        match reason {
+            StopReason::WaitingForInput => Ok(Reply::Text(text)),
+            StopReason::Halt => Ok(Reply::Halted(text)),
+            _ => Ok(Reply::Stuck),
+        }
+    }
+
+    fn command(&mut self, command: &str) -> Result<Reply> {
+        self.input.push_line(command);
+        self.run()
+    }
+
+    /// Send a command the droid is expected to survive
+    fn expect(&mut self, command: &str) -> Result<String> {
+        match self.command(command)? {
+            Reply::Text(text) => Ok(text),
+            Reply::Halted(text) => Err(anyhow!("the game ended after '{}': {}", command, text)),
+            Reply::Stuck => Err(anyhow!("the droid got stuck after '{}'", command)),
+        }
+    }
+
+    /// Pick up an item, unless it turns out to be deadly
+    fn take(&mut self, item: &str) -> Result<()> {
+        let snapshot = self.droid.snapshot();
+        match self.command(&format!("take {}", item))? {
+            Reply::Text(text) if text.contains("You take the") => {
+                self.inventory.push(item.to_string());
+            }
+            _ => {
+                self.droid.restore(&snapshot);
+                self.input.clear();
+                self.output.take_text();
+                self.deadly.insert(item.to_string());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn drop(&mut self, item: &str) -> Result<()> {
+        self.expect(&format!("drop {}", item))?;
+        self.inventory.retain(|i| i != item);
+        Ok(())
+    }
+
+    /// The solution once the game ended with `text`
+    fn solution(&self, text: &str, attempts: usize) -> Result<Solution> {
+        let password = text
+            .split_whitespace()
+            .find(|word| word.chars().all(|c| c.is_ascii_digit()))
+            .ok_or_else(|| anyhow!("no password in: {}", text))?;
+
+        Ok(Solution {
+            password: password.to_string(),
+            items: self.inventory.clone(),
+            attempts,
+        })
+    }
+
+    /// Visit every room reachable from `room`, taking every item that's safe, and come back.
+    /// `entrance` is the room the droid came from and the door it took. If the droid happens to
+    /// get past the floor on the way, that's the solution.
+    fn explore(
+        &mut self,
+        room: Room,
+        entrance: Option<(&str, &str)>,
+        path: &mut Vec<String>,
+    ) -> Result<Option<Solution>> {
+        for item in &room.items {
+            if !self.deadly.contains(item) {
+                self.take(item)?;
+            }
+        }
+
+        let name = room.name.clone();
+        let doors = room.doors.clone();
+        self.ship.insert(name.clone(), (room, HashMap::new()));
+        if let Some((from, door)) = entrance {
+            self.link(from, door, &name)?;
+        }
+
+        for door in doors {
+            if self.ship[&name].1.contains_key(&door) {
+                continue;
+            }
+
+            let text = match self.command(&door)? {
+                Reply::Text(text) => text,
+                // everything that's safe to carry may just be the right weight
+                Reply::Halted(text) => return self.solution(&text, 1).map(Some),
+                Reply::Stuck => return Err(anyhow!("the droid got stuck after '{}'", door)),
+            };
+            let next = Room::parse(&text)
+                .ok_or_else(|| anyhow!("no room after going {}: {}", door, text))?;
+
+            // the floor throws the droid straight back into the checkpoint
+            if text.contains(TOO_LIGHT) || text.contains(TOO_HEAVY) {
+                self.checkpoint = Some(path.clone());
+                self.floor = Some(door.clone());
+                continue;
+            }
+
+            if self.ship.contains_key(&next.name) {
+                self.link(&name, &door, &next.name)?;
+                self.expect(opposite(&door)?)?;
+                continue;
+            }
+
+            path.push(door.clone());
+            if let Some(solution) = self.explore(next, Some((&name, &door)), path)? {
+                return Ok(Some(solution));
+            }
+            path.pop();
+            self.expect(opposite(&door)?)?;
+        }
+
+        Ok(None)
+    }
+
+    fn link(&mut self, from: &str, door: &str, to: &str) -> Result<()> {
+        if let Some((_, doors)) = self.ship.get_mut(from) {
+            doors.insert(door.to_string(), to.to_string());
+        }
+        if let Some((_, doors)) = self.ship.get_mut(to) {
+            doors.insert(opposite(door)?.to_string(), from.to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Carry exactly the items in `mask` (bit `i` is `items[i]`)
+    fn carry(&mut self, items: &[String], mask: u32) -> Result<()> {
+        for (i, item) in items.iter().enumerate() {
+            let carrying = self.inventory.contains(item);
+            match mask & (1 << i) != 0 {
+                true if !carrying => self.take(item)?,
+                false if carrying => self.drop(item)?,
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Map the ship, bring every safe item to the checkpoint and find the items that get the droid
+    /// past the floor
+    pub fn solve(&mut self) -> Result<Solution> {
+        let start = match self.run()? {
+            Reply::Text(text) => Room::parse(&text).ok_or_else(|| anyhow!("no room: {}", text))?,
+            _ => return Err(anyhow!("the droid didn't start in a room")),
+        };
+        if let Some(solution) = self.explore(start, None, &mut vec![])? {
+            return Ok(solution);
+        }
+
+        let (path, floor) = match (self.checkpoint.clone(), self.floor.clone()) {
+            (Some(path), Some(floor)) => (path, floor),
+            _ => return Err(anyhow!("couldn't find the security checkpoint")),
+        };
+        for door in &path {
+            self.expect(door)?;
+        }
+
+        // a set of items that's too light means all of its subsets are too, and one that's too
+        // heavy means all of its supersets are
+        let items = self.inventory.clone();
+        if items.len() > MAX_ITEMS {
+            return Err(anyhow!(
+                "too many items to try all their combinations: {}",
+                items.len()
+            ));
+        }
+        let mut masks = (0..1u32 << items.len()).collect::<Vec<_>>();
+        // starting from the middle sizes lets both kinds of answers rule out other sets
+        let middle = items.len() as i32 / 2;
+        masks.sort_by_key(|mask| (mask.count_ones() as i32 - middle).abs());
+        let mut too_light = vec![];
+        let mut too_heavy = vec![];
+        let mut attempts = 0;
+        for mask in masks {
+            if too_light.iter().any(|light| mask & !light == 0)
+                || too_heavy.iter().any(|heavy| heavy & !mask == 0)
+            {
+                continue;
+            }
+
+            self.carry(&items, mask)?;
+            attempts += 1;
+            match self.command(&floor)? {
+                Reply::Text(text) if text.contains(TOO_LIGHT) => too_light.push(mask),
+                Reply::Text(text) if text.contains(TOO_HEAVY) => too_heavy.push(mask),
+                Reply::Halted(text) => return self.solution(&text, attempts),
+                Reply::Text(text) => return Err(anyhow!("unexpected reply: {}", text)),
+                Reply::Stuck => return Err(anyhow!("the droid got stuck on the floor")),
+            }
+        }
+
+        Err(anyhow!("no combination of items gets past the floor"))
+    }
+}
@@ -0,0 +1,501 @@
+//! An assembler for intcode, using the same syntax as the disassembler's listings:
+//!
+//! ```text
+//! ; comments start with a semicolon
+//! .macro push value        ; macros are expanded textually, `\name` is replaced by the argument
+//!     add \value, #0, rb+0 ; and `\@` by a number that's unique to each expansion
+//!     arb #1
+//! .endmacro                ; macros can invoke the macros defined before them
+//!
+//! start:  in [x]           ; parameters are [position], #immediate or rb+offset
+//!         push [x]
+//!         jt [x], #start   ; labels can be used anywhere a number can, e.g. `#label+1`
+//!         halt
+//! x:      data 0, 1, "hi"  ; strings are stored as one int per character
+//! ```
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::mem;
+
+use itertools::Itertools;
+
+use super::int_code::IntCode;
+use super::op_code::OpCode;
+use super::Int;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsmError {
+    /// Line of the source the error is on (starting at 1)
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for AsmError {}
+
+/// Where the ints of a single source line ended up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListingEntry {
+    pub address: usize,
+    pub len: usize,
+    /// Line of the source (starting at 1), lines expanded from a macro point at its invocation
+    pub line: usize,
+    pub source: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assembly {
+    /// The assembled program, ready for `Program::new`
+    pub ints: Vec<Int>,
+    pub labels: HashMap<String, usize>,
+    pub entries: Vec<ListingEntry>,
+}
+
+impl Assembly {
+    /// A printable listing mapping each address back to its source line
+    pub fn listing(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| {
+                let raw = self.ints[entry.address..entry.address + entry.len]
+                    .iter()
+                    .join(",");
+                format!(
+                    "{:>5}  {:<24}  {:>4} | {}\n",
+                    entry.address,
+                    raw,
+                    entry.line,
+                    entry.source.trim()
+                )
+            })
+            .collect()
+    }
+}
+
+/// A line of source after macros are expanded and labels and comments are removed
+#[derive(Debug)]
+struct Statement {
+    address: usize,
+    line: usize,
+    source: String,
+    mnemonic: String,
+    operands: Vec<String>,
+}
+
+struct Macro {
+    params: Vec<String>,
+    body: Vec<String>,
+}
+
+fn error<T>(line: usize, message: impl Into<String>) -> Result<T, AsmError> {
+    Err(AsmError {
+        line,
+        message: message.into(),
+    })
+}
+
+/// Remove a trailing comment, ignoring semicolons inside strings
+fn strip_comment(s: &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            ';' if !in_string => return &s[..i],
+            _ => {}
+        }
+    }
+
+    s
+}
+
+/// Split operands on commas, ignoring commas inside strings
+fn split_operands(s: &str) -> Vec<String> {
+    if s.trim().is_empty() {
+        return vec![];
+    }
+
+    let mut operands = vec![];
+    let mut current = String::new();
+    let mut in_string = false;
+    for c in s.chars() {
+        match c {
+            '"' => {
+                in_string = !in_string;
+                current.push(c);
+            }
+            ',' if !in_string => operands.push(mem::take(&mut current).trim().to_string()),
+            _ => current.push(c),
+        }
+    }
+    operands.push(current.trim().to_string());
+
+    operands
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// How deeply macros may invoke other macros, which catches a macro that invokes itself
+const MAX_MACRO_DEPTH: usize = 32;
+
+/// The lines expanded so far as `(line, source, text)`, and how many macros were expanded
+#[derive(Default)]
+struct Expansion {
+    lines: Vec<(usize, String, String)>,
+    count: usize,
+}
+
+/// Replace `\@` by `unique` and each `\param` by its argument in a single pass, so a parameter
+/// that's a prefix of another (like `a` and `ab`) can't clobber it. Unknown names are left as is.
+fn substitute(body_line: &str, unique: &str, args: &HashMap<&str, &str>) -> String {
+    let mut result = String::with_capacity(body_line.len());
+    let mut rest = body_line;
+    while let Some(i) = rest.find('\\') {
+        result.push_str(&rest[..i]);
+        let after = &rest[i + 1..];
+        if let Some(after) = after.strip_prefix('@') {
+            result.push_str(unique);
+            rest = after;
+            continue;
+        }
+
+        let len = after
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(after.len());
+        match args.get(&after[..len]) {
+            Some(arg) => result.push_str(arg),
+            None => result.push_str(&rest[i..i + 1 + len]),
+        }
+        rest = &after[len..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Expand `code` if it invokes a macro, along with any macros its body invokes in turn
+fn expand(
+    line: usize,
+    text: &str,
+    code: &str,
+    macros: &HashMap<String, Macro>,
+    depth: usize,
+    expansion: &mut Expansion,
+) -> Result<(), AsmError> {
+    // a label may come before a macro invocation
+    let (label, rest) = match code.split_once(':') {
+        Some((label, rest)) if is_identifier(label.trim()) => (Some(label.trim()), rest.trim()),
+        _ => (None, code),
+    };
+    let (name, args) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let m = match macros.get(name) {
+        Some(m) => m,
+        None => {
+            expansion
+                .lines
+                .push((line, text.to_string(), code.to_string()));
+            return Ok(());
+        }
+    };
+
+    if depth == MAX_MACRO_DEPTH {
+        return error(
+            line,
+            format!(
+                "macro '{}' is nested too deeply, does it invoke itself?",
+                name
+            ),
+        );
+    }
+    let args = split_operands(args);
+    if args.len() != m.params.len() {
+        return error(
+            line,
+            format!(
+                "macro '{}' takes {} arguments but got {}",
+                name,
+                m.params.len(),
+                args.len()
+            ),
+        );
+    }
+
+    expansion.count += 1;
+    if let Some(label) = label {
+        expansion
+            .lines
+            .push((line, text.to_string(), format!("{}:", label)));
+    }
+    let unique = expansion.count.to_string();
+    let args = m
+        .params
+        .iter()
+        .map(String::as_str)
+        .zip(args.iter().map(String::as_str))
+        .collect::<HashMap<_, _>>();
+    for body_line in &m.body {
+        let body_line = substitute(body_line, &unique, &args);
+        expand(line, text, body_line.trim(), macros, depth + 1, expansion)?;
+    }
+
+    Ok(())
+}
+
+/// Expand macros and turn each line into `(line, source, text)`, keeping the line of the
+/// invocation for lines that came from a macro. Macros can invoke any macro that's defined before
+/// the invocation.
+fn expand_macros(source: &str) -> Result<Vec<(usize, String, String)>, AsmError> {
+    let mut macros: HashMap<String, Macro> = HashMap::new();
+    let mut expansion = Expansion::default();
+
+    let mut lines = source.lines().enumerate().map(|(i, l)| (i + 1, l));
+    while let Some((line, text)) = lines.next() {
+        let code = strip_comment(text).trim();
+        let mut words = code.splitn(2, char::is_whitespace);
+        match words.next() {
+            Some(".macro") => {
+                let rest = words.next().unwrap_or("").trim();
+                let (name, params) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                if !is_identifier(name) {
+                    return error(line, "expected a macro name");
+                }
+                let name = name.to_string();
+                let params = match params.trim() {
+                    "" => vec![],
+                    params => params.split(',').map(|p| p.trim().to_string()).collect(),
+                };
+                if let Some(param) = params.iter().find(|p| !is_identifier(p)) {
+                    return error(line, format!("invalid macro parameter '{}'", param));
+                }
+
+                let mut body = vec![];
+                loop {
+                    match lines.next() {
+                        Some((_, text)) if strip_comment(text).trim() == ".endmacro" => break,
+                        Some((_, text)) => body.push(strip_comment(text).to_string()),
+                        None => return error(line, format!("macro '{}' is never ended", name)),
+                    }
+                }
+
+                macros.insert(name, Macro { params, body });
+            }
+            Some(".endmacro") => return error(line, "'.endmacro' without a '.macro'"),
+            _ => expand(line, text, code, &macros, 0, &mut expansion)?,
+        }
+    }
+
+    Ok(expansion.lines)
+}
+
+/// Split the labels off each line and collect the statements
+fn parse(source: &str) -> Result<(Vec<Statement>, HashMap<String, usize>), AsmError> {
+    let mut statements = vec![];
+    let mut labels = HashMap::new();
+    let mut address = 0;
+
+    for (line, source, mut code) in expand_macros(source)? {
+        while let Some((label, rest)) = code.split_once(':') {
+            let label = label.trim();
+            if !is_identifier(label) {
+                break;
+            }
+            if labels.insert(label.to_string(), address).is_some() {
+                return error(line, format!("label '{}' is defined more than once", label));
+            }
+            code = rest.to_string();
+        }
+
+        let code = code.trim();
+        if code.is_empty() {
+            continue;
+        }
+
+        let (mnemonic, operands) = code.split_once(char::is_whitespace).unwrap_or((code, ""));
+        let statement = Statement {
+            address,
+            line,
+            source,
+            mnemonic: mnemonic.to_lowercase(),
+            operands: split_operands(operands),
+        };
+
+        address += statement_len(&statement)?;
+        statements.push(statement);
+    }
+
+    Ok((statements, labels))
+}
+
+fn string_literal(operand: &str) -> Option<&str> {
+    operand.strip_prefix('"').and_then(|s| s.strip_suffix('"'))
+}
+
+fn statement_len(statement: &Statement) -> Result<usize, AsmError> {
+    if statement.mnemonic == "data" {
+        return Ok(statement
+            .operands
+            .iter()
+            .map(|operand| string_literal(operand).map_or(1, |s| s.chars().count()))
+            .sum());
+    }
+
+    match OpCode::<Int>::from_mnemonic(&statement.mnemonic) {
+        Some(op_code) => Ok(op_code.len()),
+        None => error(
+            statement.line,
+            format!("unknown instruction '{}'", statement.mnemonic),
+        ),
+    }
+}
+
+/// Evaluate a sum of numbers and labels, e.g. `-1`, `label` or `label+2`
+fn eval(expr: &str, labels: &HashMap<String, usize>) -> Result<Int, String> {
+    let expr = expr.replace(char::is_whitespace, "");
+    if expr.is_empty() {
+        return Err(String::from("expected a value"));
+    }
+
+    // terms are summed as `i128`s so `Int::MIN` can be written out, and any overflow is caught
+    let mut total: i128 = 0;
+    let mut rest = expr.as_str();
+    while !rest.is_empty() {
+        let sign = match rest.chars().next() {
+            Some('-') => -1,
+            Some('+') => 1,
+            _ if rest.len() == expr.len() => 1,
+            _ => return Err(format!("invalid expression '{}'", expr)),
+        };
+        rest = rest.strip_prefix(['+', '-']).unwrap_or(rest);
+
+        let end = rest.find(['+', '-']).unwrap_or(rest.len());
+        let term = &rest[..end];
+        let value = match term.parse::<i128>() {
+            Ok(value) => value,
+            Err(_) if is_identifier(term) => match labels.get(term) {
+                Some(address) => *address as i128,
+                None => return Err(format!("unknown label '{}'", term)),
+            },
+            Err(_) => return Err(format!("invalid value '{}'", term)),
+        };
+
+        total = match sign {
+            1 => total.checked_add(value),
+            _ => total.checked_sub(value),
+        }
+        .ok_or_else(|| format!("'{}' overflows", expr))?;
+        rest = &rest[end..];
+    }
+
+    Int::try_from(total).map_err(|_| format!("'{}' overflows", expr))
+}
+
+/// Encode a parameter as `(mode, value)`
+fn parameter(operand: &str, labels: &HashMap<String, usize>) -> Result<(Int, Int), String> {
+    let operand = operand.trim();
+    if let Some(address) = operand.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+        match eval(address, labels)? {
+            address if address < 0 => Err(format!("negative address {}", address)),
+            address => Ok((0, address)),
+        }
+    } else if let Some(value) = operand.strip_prefix('#') {
+        Ok((1, eval(value, labels)?))
+    } else if operand == "rb" {
+        Ok((2, 0))
+    } else if let Some(offset) = operand
+        .strip_prefix("rb")
+        .filter(|s| s.trim_start().starts_with(['+', '-']))
+    {
+        Ok((2, eval(offset, labels)?))
+    } else {
+        Err(format!(
+            "'{}' needs a parameter mode: [position], #immediate or rb+offset",
+            operand
+        ))
+    }
+}
+
+fn encode(statement: &Statement, labels: &HashMap<String, usize>) -> Result<Vec<Int>, AsmError> {
+    let fail = |message: String| AsmError {
+        line: statement.line,
+        message,
+    };
+
+    if statement.mnemonic == "data" {
+        let mut ints = vec![];
+        for operand in &statement.operands {
+            match string_literal(operand) {
+                Some(s) => ints.extend(s.chars().map(|c| c as Int)),
+                None => ints.push(eval(operand, labels).map_err(fail)?),
+            }
+        }
+        return Ok(ints);
+    }
+
+    let op_code = OpCode::<Int>::from_mnemonic(&statement.mnemonic)
+        .ok_or_else(|| fail(format!("unknown instruction '{}'", statement.mnemonic)))?;
+    let mnemonic = op_code.mnemonic();
+    let arity = op_code.len() - 1;
+    if statement.operands.len() != arity {
+        return Err(fail(format!(
+            "'{}' takes {} parameters but got {}",
+            mnemonic,
+            arity,
+            statement.operands.len()
+        )));
+    }
+    // the parameters of `from_mnemonic` are numbered, so the target names its own index
+    let target = match op_code.target() {
+        Some(IntCode::Position(i)) => Some(i),
+        _ => None,
+    };
+
+    let mut ints = vec![op_code.code()];
+    let mut place = 100;
+    for (i, operand) in statement.operands.iter().enumerate() {
+        let (mode, value) = parameter(operand, labels).map_err(fail)?;
+        if mode == 1 && target == Some(i) {
+            return Err(fail(format!(
+                "'{}' writes to its parameter {}, so it can't be immediate",
+                mnemonic, i
+            )));
+        }
+
+        ints[0] += mode * place;
+        ints.push(value);
+        place *= 10;
+    }
+
+    Ok(ints)
+}
+
+pub fn assemble(source: &str) -> Result<Assembly, AsmError> {
+    let (statements, labels) = parse(source)?;
+
+    let mut ints = vec![];
+    let mut entries = vec![];
+    for statement in &statements {
+        let encoded = encode(statement, &labels)?;
+        entries.push(ListingEntry {
+            address: statement.address,
+            len: encoded.len(),
+            line: statement.line,
+            source: statement.source.clone(),
+        });
+        ints.extend(encoded);
+    }
+
+    Ok(Assembly {
+        ints,
+        labels,
+        entries,
+    })
+}
@@ -0,0 +1,361 @@
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+use _2019::cfg::Graph;
+use _2019::compile::Compiled;
+use _2019::diff::Diff;
+use _2019::trace::{BinaryTracer, JsonlTracer, Profile, Tracer};
+use _2019::{asm, debugger::Debugger, disasm, Int, IntcodeError, Program, Snapshot, StopReason};
+
+/// Tools for working with the intcode programs of aoc 2019
+#[derive(Parser)]
+struct Args {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Assemble a program and print its ints (or write them to a file)
+    Asm {
+        path: PathBuf,
+
+        /// Where to write the assembled program
+        #[clap(short = 'o', long = "output")]
+        output: Option<PathBuf>,
+
+        /// Print a listing mapping each address back to its source line
+        #[clap(short = 'l', long = "listing")]
+        listing: bool,
+    },
+    /// Print a disassembly listing of a program
+    Disasm { path: PathBuf },
+    /// Print the control-flow graph of a program as a listing of its basic blocks
+    Cfg {
+        path: PathBuf,
+
+        /// Print it in Graphviz's DOT language instead
+        #[clap(long = "dot")]
+        dot: bool,
+    },
+    /// Step through a program interactively (type `help` for the commands)
+    Debug {
+        path: PathBuf,
+
+        /// Comma separated ints to start the input with
+        #[clap(short = 'i', long = "input", use_delimiter = true)]
+        input: Vec<Int>,
+    },
+    /// Run a program, feeding it the given input and then lines from stdin when it needs more
+    Run {
+        path: PathBuf,
+
+        /// Comma separated ints to start the input with
+        #[clap(short = 'i', long = "input", use_delimiter = true)]
+        input: Vec<Int>,
+
+        /// Read and write ASCII instead of ints
+        #[clap(short = 'a', long = "ascii")]
+        ascii: bool,
+
+        /// Write every executed instruction to a file
+        #[clap(long = "trace")]
+        trace: Option<PathBuf>,
+
+        #[clap(long = "trace-format", default_value = "jsonl", possible_values = &["jsonl", "binary"])]
+        trace_format: String,
+
+        /// Print instruction counts per opcode and the hottest addresses once the program stops
+        #[clap(short = 'p', long = "profile")]
+        profile: bool,
+
+        /// Stop the program after this many instructions (e.g. to trace an infinite loop)
+        #[clap(long = "max-steps")]
+        max_steps: Option<u64>,
+
+        /// Fail when an addition, multiplication or the relative base overflows instead of wrapping
+        #[clap(long = "checked")]
+        checked: bool,
+
+        /// Print a listing marking the instructions that executed once the program stops
+        #[clap(short = 'c', long = "coverage")]
+        coverage: bool,
+
+        /// Print the memory that changed once the program stops
+        #[clap(short = 'd', long = "diff")]
+        diff: bool,
+    },
+    /// Print the memory that differs between two programs or snapshots
+    Diff { before: PathBuf, after: PathBuf },
+    /// Time a program with and without the decoded instruction cache, and compiled to closures
+    Bench {
+        path: PathBuf,
+
+        /// Comma separated ints to give the program as input
+        #[clap(short = 'i', long = "input", use_delimiter = true)]
+        input: Vec<Int>,
+
+        /// How many times to run the program with each
+        #[clap(short = 'r', long = "runs", default_value = "10")]
+        runs: usize,
+    },
+}
+
+fn read_program(path: &Path) -> Result<Vec<Int>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| anyhow!("failed to read {}: {}", path.display(), e))?;
+
+    contents
+        .trim()
+        .split(',')
+        .map(|n| {
+            n.trim()
+                .parse::<Int>()
+                .map_err(|e| anyhow!("invalid int '{}': {}", n.trim(), e))
+        })
+        .collect()
+}
+
+/// A snapshot's memory or a program's ints, whichever `path` holds
+fn read_memory(path: &Path) -> Result<Snapshot> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| anyhow!("failed to read {}: {}", path.display(), e))?;
+    // snapshots are JSON objects, programs are comma separated ints
+    match contents.trim_start().starts_with('{') {
+        true => Snapshot::load(path),
+        false => Ok(Program::new(read_program(path)?).snapshot()),
+    }
+}
+
+fn assemble(path: &Path, output: Option<PathBuf>, listing: bool) -> Result<()> {
+    let source = fs::read_to_string(path)
+        .map_err(|e| anyhow!("failed to read {}: {}", path.display(), e))?;
+    let assembly = asm::assemble(&source).map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+
+    let ints = format!(
+        "{}\n",
+        assembly
+            .ints
+            .iter()
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    );
+    match output {
+        Some(output) => fs::write(output, ints)?,
+        None if !listing => print!("{}", ints),
+        None => {}
+    }
+    if listing {
+        print!("{}", assembly.listing());
+    }
+
+    Ok(())
+}
+
+fn print_output(output: &[Int], ascii: bool) -> Result<()> {
+    let mut stdout = io::stdout();
+    for value in output {
+        match (ascii, u8::try_from(*value)) {
+            (true, Ok(byte)) if byte.is_ascii() => write!(stdout, "{}", byte as char)?,
+            _ => writeln!(stdout, "{}", value)?,
+        }
+    }
+
+    Ok(stdout.flush()?)
+}
+
+fn run<T: Tracer>(
+    program: &mut Program,
+    input: Vec<Int>,
+    ascii: bool,
+    max_steps: Option<u64>,
+    tracer: &mut T,
+) -> Result<()> {
+    let mut input = VecDeque::from(input);
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut output = vec![];
+    let mut steps = 0;
+
+    loop {
+        if max_steps.is_some_and(|max| steps >= max) {
+            return Err(anyhow!("stopped after {} steps", steps));
+        }
+
+        steps += 1;
+        let reason = program.step_traced(&mut &mut input, &mut &mut output, tracer)?;
+        if !output.is_empty() {
+            print_output(&output, ascii)?;
+            output.clear();
+        }
+
+        match reason {
+            None | Some(StopReason::Output(_)) | Some(StopReason::BudgetExhausted) => {}
+            Some(StopReason::Halt) => return Ok(()),
+            Some(StopReason::WaitingForInput) => {
+                let line = lines
+                    .next()
+                    .ok_or_else(|| anyhow!("the program is waiting for input"))??;
+
+                if ascii {
+                    input.extend(line.bytes().map(Int::from));
+                    input.push_back(Int::from(b'\n'));
+                } else {
+                    for n in line.split(',').filter(|n| !n.trim().is_empty()) {
+                        input.push_back(n.trim().parse()?);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Run a program (set up by `program`) to completion `runs` times, returns its output and the
+/// fastest run
+fn time<F>(runs: usize, mut program: F) -> Result<(Vec<Int>, Duration)>
+where
+    F: FnMut(&mut Vec<Int>) -> Result<StopReason, IntcodeError>,
+{
+    let mut output = vec![];
+    let mut fastest = Duration::MAX;
+    for _ in 0..runs.max(1) {
+        output.clear();
+        let start = Instant::now();
+        let reason = program(&mut output)?;
+        fastest = fastest.min(start.elapsed());
+
+        if reason != StopReason::Halt {
+            return Err(anyhow!("the program stopped with {:?}", reason));
+        }
+    }
+
+    Ok((output, fastest))
+}
+
+fn bench(path: &Path, input: Vec<Int>, runs: usize) -> Result<()> {
+    let ints = read_program(path)?;
+    let interpreted = |cache| {
+        time(runs, |output| {
+            let mut program = Program::new(ints.clone()).with_decode_cache(cache);
+            program.run(VecDeque::from(input.clone()), output)
+        })
+    };
+    let (expected, uncached) = interpreted(false)?;
+    let (cached_output, cached) = interpreted(true)?;
+    let (compiled_output, compiled) = time(runs, |output| {
+        Compiled::new(ints.clone()).run(VecDeque::from(input.clone()), output)
+    })?;
+
+    for (name, output) in [("cached", cached_output), ("compiled", compiled_output)] {
+        if output != expected {
+            return Err(anyhow!(
+                "the {} run output {:?} instead of {:?}",
+                name,
+                output,
+                expected
+            ));
+        }
+    }
+
+    println!("decoding every step  {:?}", uncached);
+    for (name, duration) in [("decode cache", cached), ("compiled closures", compiled)] {
+        println!(
+            "{:<20} {:?} ({:.2}x)",
+            name,
+            duration,
+            uncached.as_secs_f64() / duration.as_secs_f64()
+        );
+    }
+
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    match Args::parse().command {
+        Command::Asm {
+            path,
+            output,
+            listing,
+        } => assemble(&path, output, listing)?,
+        Command::Disasm { path } => print!("{}", disasm::listing(&read_program(&path)?)),
+        Command::Cfg { path, dot } => {
+            let graph = Graph::new(&read_program(&path)?);
+            match dot {
+                true => print!("{}", graph.to_dot()),
+                false => print!("{}", graph),
+            }
+        }
+        Command::Debug { path, input } => {
+            let mut program = Program::new(read_program(&path)?);
+            let mut debugger = Debugger::new(&mut program);
+            debugger.push_input(input);
+            debugger.repl(io::stdin().lock(), io::stdout())?;
+        }
+        Command::Bench { path, input, runs } => bench(&path, input, runs)?,
+        Command::Diff { before, after } => {
+            print!(
+                "{}",
+                Diff::between(&read_memory(&before)?, &read_memory(&after)?)
+            )
+        }
+        Command::Run {
+            path,
+            input,
+            ascii,
+            trace,
+            trace_format,
+            profile,
+            max_steps,
+            checked,
+            coverage,
+            diff,
+        } => {
+            let ints = read_program(&path)?;
+            let mut program = Program::new(ints.clone())
+                .with_checked_arithmetic(checked)
+                .with_coverage(coverage);
+            let program = &mut program;
+            let mut profiler = Profile::new();
+            // finish writing the trace and print the profile even if the program failed
+            let result = match trace {
+                Some(trace) => {
+                    let file = BufWriter::new(File::create(&trace)?);
+                    if trace_format == "jsonl" {
+                        let mut tracer = JsonlTracer::new(file);
+                        let both = &mut (&mut profiler, &mut tracer);
+                        let result = run(program, input, ascii, max_steps, both);
+                        tracer.finish()?;
+                        result
+                    } else {
+                        let mut tracer = BinaryTracer::new(file);
+                        let both = &mut (&mut profiler, &mut tracer);
+                        let result = run(program, input, ascii, max_steps, both);
+                        tracer.finish()?;
+                        result
+                    }
+                }
+                None if profile => run(program, input, ascii, max_steps, &mut profiler),
+                None => run(program, input, ascii, max_steps, &mut ()),
+            };
+
+            if profile {
+                eprint!("\n{}", profiler);
+            }
+            if let Some(coverage) = program.coverage() {
+                eprint!("\n{}", coverage.listing(&ints));
+            }
+            if diff {
+                eprint!("\n{}", Diff::new(&ints, &program.get_memory()));
+            }
+            result?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+//! Decoded instructions by address, so a program doesn't decode the same instruction every time it
+//! runs it. Intcode can modify itself, so every write invalidates the instructions it overlaps.
+
+use super::op_code::OpCode;
+use super::word::Word;
+use super::Int;
+
+/// The longest an instruction can be, in ints
+const MAX_LEN: usize = 4;
+
+#[derive(Debug, Clone)]
+pub struct DecodeCache<W = Int> {
+    op_codes: Vec<Option<OpCode<W>>>,
+}
+
+impl<W> Default for DecodeCache<W> {
+    fn default() -> DecodeCache<W> {
+        DecodeCache { op_codes: vec![] }
+    }
+}
+
+impl<W: Word> DecodeCache<W> {
+    #[inline]
+    pub fn get(&self, ip: usize) -> Option<OpCode<W>> {
+        self.op_codes.get(ip).cloned().flatten()
+    }
+
+    #[inline]
+    pub fn insert(&mut self, ip: usize, op_code: OpCode<W>) {
+        if ip >= self.op_codes.len() {
+            self.op_codes.resize(ip + 1, None);
+        }
+        self.op_codes[ip] = Some(op_code);
+    }
+
+    /// Forget any instruction that `address` is a part of
+    #[inline]
+    pub fn invalidate(&mut self, address: usize) {
+        let start = address.saturating_sub(MAX_LEN - 1);
+        let end = (address + 1).min(self.op_codes.len());
+        if start < end {
+            self.op_codes[start..end].fill(None);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.op_codes.clear();
+    }
+}
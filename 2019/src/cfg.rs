@@ -0,0 +1,389 @@
+//! A control-flow graph of a program, recovered statically by following every path from the start.
+//!
+//! Only jumps to an immediate destination can be followed. The call idiom that intcode compilers use
+//! (store the return address relative to rb, then jump) is recognised so the return address is
+//! followed too, and a jump to an address read relative to rb is taken to be a return.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use super::disasm::{Kind, Line};
+use super::int_code::IntCode;
+use super::op_code::OpCode;
+use super::Int;
+
+/// How control leaves a basic block
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Exit {
+    /// Runs straight into the block starting at this address
+    Fallthrough(usize),
+    Jump(usize),
+    Branch {
+        taken: usize,
+        fallthrough: usize,
+    },
+    /// A jump to `callee` right after storing `returns_to` relative to rb
+    Call {
+        callee: usize,
+        returns_to: usize,
+    },
+    /// A jump to an address read relative to rb, i.e. one stored by a call
+    Return {
+        fallthrough: Option<usize>,
+    },
+    /// A jump to an address read from memory, which can't be followed
+    Indirect {
+        fallthrough: Option<usize>,
+    },
+    Halt,
+    /// The block's first address doesn't decode to an instruction
+    Invalid,
+}
+
+impl Exit {
+    /// The blocks control can go to next, along with a label for each edge
+    pub fn successors(&self) -> Vec<(usize, &'static str)> {
+        match *self {
+            Exit::Fallthrough(next) => vec![(next, "")],
+            Exit::Jump(destination) => vec![(destination, "jump")],
+            Exit::Branch { taken, fallthrough } => {
+                vec![(taken, "taken"), (fallthrough, "not taken")]
+            }
+            Exit::Call { callee, returns_to } => vec![(callee, "call"), (returns_to, "returns to")],
+            Exit::Return { fallthrough } | Exit::Indirect { fallthrough } => fallthrough
+                .map(|next| (next, "not taken"))
+                .into_iter()
+                .collect(),
+            Exit::Halt | Exit::Invalid => vec![],
+        }
+    }
+}
+
+impl fmt::Display for Exit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Exit::Fallthrough(next) => write!(f, "falls through to {}", next),
+            Exit::Jump(destination) => write!(f, "jumps to {}", destination),
+            Exit::Branch { taken, fallthrough } => {
+                write!(f, "branches to {} or {}", taken, fallthrough)
+            }
+            Exit::Call { callee, returns_to } => {
+                write!(f, "calls {}, returning to {}", callee, returns_to)
+            }
+            Exit::Return { fallthrough: None } => write!(f, "returns"),
+            Exit::Return {
+                fallthrough: Some(next),
+            } => write!(f, "returns or falls through to {}", next),
+            Exit::Indirect { fallthrough: None } => write!(f, "jumps indirectly"),
+            Exit::Indirect {
+                fallthrough: Some(next),
+            } => write!(f, "jumps indirectly or falls through to {}", next),
+            Exit::Halt => write!(f, "halts"),
+            Exit::Invalid => write!(f, "invalid instruction"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub start: usize,
+    /// Every line is a `Kind::Instruction`, and there are none if the block is `Exit::Invalid`
+    pub lines: Vec<Line>,
+    pub exit: Exit,
+}
+
+impl Block {
+    /// The address right after the block's last instruction
+    pub fn end(&self) -> usize {
+        self.lines
+            .last()
+            .map_or(self.start, |line| line.address + line.ints.len())
+    }
+}
+
+/// An instruction that writes into memory that's also reached as an instruction
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SelfModification {
+    /// The instruction doing the write
+    pub ip: usize,
+    pub address: usize,
+    /// The instruction that's written into
+    pub instruction: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Graph {
+    /// Every reachable basic block by its start address
+    pub blocks: BTreeMap<usize, Block>,
+    /// Writes to code, only for position parameters as relative ones depend on rb
+    pub self_modifications: Vec<SelfModification>,
+}
+
+/// Whether a jump with this test parameter is always (`Some(true)`) or never taken
+fn constant_test(test: &IntCode, jumps_if_true: bool) -> Option<bool> {
+    match test {
+        IntCode::Immediate(value) => Some((*value != 0) == jumps_if_true),
+        _ => None,
+    }
+}
+
+/// The constant an instruction stores relative to rb, if it computes one from immediates
+fn stored_constant(op_code: &OpCode) -> Option<Int> {
+    match *op_code {
+        OpCode::Add {
+            lhs: IntCode::Immediate(lhs),
+            rhs: IntCode::Immediate(rhs),
+            target: IntCode::Relative(_),
+        } => lhs.checked_add(rhs),
+        OpCode::Mult {
+            lhs: IntCode::Immediate(lhs),
+            rhs: IntCode::Immediate(rhs),
+            target: IntCode::Relative(_),
+        } => lhs.checked_mul(rhs),
+        _ => None,
+    }
+}
+
+/// How control leaves the instruction at `ip`, or `None` if it always carries on to the next one
+fn exit(ip: usize, op_code: &OpCode, previous: Option<&OpCode>) -> Option<Exit> {
+    let next = ip + op_code.len();
+    let (test, destination, jumps_if_true) = match *op_code {
+        OpCode::JumpIfTrue { test, destination } => (test, destination, true),
+        OpCode::JumpIfFalse { test, destination } => (test, destination, false),
+        OpCode::Halt => return Some(Exit::Halt),
+        _ => return None,
+    };
+
+    let always = match constant_test(&test, jumps_if_true) {
+        Some(false) => return None,
+        Some(true) => true,
+        None => false,
+    };
+    let fallthrough = (!always).then_some(next);
+    Some(match destination {
+        IntCode::Immediate(destination) if destination < 0 => Exit::Indirect { fallthrough },
+        IntCode::Immediate(destination) => {
+            let destination = destination as usize;
+            let returns_to = previous.and_then(stored_constant);
+            match fallthrough {
+                Some(fallthrough) => Exit::Branch {
+                    taken: destination,
+                    fallthrough,
+                },
+                None if returns_to == Some(next as Int) => Exit::Call {
+                    callee: destination,
+                    returns_to: next,
+                },
+                None => Exit::Jump(destination),
+            }
+        }
+        IntCode::Relative(_) => Exit::Return { fallthrough },
+        IntCode::Position(_) => Exit::Indirect { fallthrough },
+    })
+}
+
+impl Graph {
+    /// Follow every path from the start of the program
+    pub fn new(ints: &[Int]) -> Graph {
+        Graph::with_entries(ints, &[0])
+    }
+
+    /// Follow every path from each of `entries`, e.g. for functions that are only called
+    /// indirectly
+    pub fn with_entries(ints: &[Int], entries: &[usize]) -> Graph {
+        let mut instructions = BTreeMap::new();
+        let mut exits = BTreeMap::new();
+        let mut leaders = entries.iter().copied().collect::<BTreeSet<_>>();
+        let mut work = entries.to_vec();
+
+        // decode every reachable instruction, following each path until it jumps away or reaches
+        // one that's already decoded
+        while let Some(start) = work.pop() {
+            let mut ip = start;
+            let mut previous = None;
+            loop {
+                // joining a path that's been followed already splits its block here
+                if instructions.contains_key(&ip) {
+                    leaders.insert(ip);
+                    break;
+                }
+
+                let mut next = ip;
+                let op_code = match OpCode::next(&mut next, ints) {
+                    Ok(op_code) => op_code,
+                    Err(_) => {
+                        leaders.insert(ip);
+                        instructions.insert(ip, None);
+                        break;
+                    }
+                };
+                instructions.insert(ip, Some(op_code));
+
+                if let Some(exit) = exit(ip, &op_code, previous.as_ref()) {
+                    for (successor, _) in exit.successors() {
+                        leaders.insert(successor);
+                        work.push(successor);
+                    }
+                    exits.insert(ip, exit);
+                    break;
+                }
+                previous = Some(op_code);
+                ip = next;
+            }
+        }
+
+        let mut blocks = BTreeMap::new();
+        for &start in &leaders {
+            let mut block = Block {
+                start,
+                lines: vec![],
+                exit: Exit::Invalid,
+            };
+            let mut ip = start;
+            while let Some(Some(op_code)) = instructions.get(&ip) {
+                let next = ip + op_code.len();
+                block.lines.push(Line {
+                    address: ip,
+                    ints: ints[ip..next].to_vec(),
+                    kind: Kind::Instruction(*op_code),
+                });
+                if let Some(exit) = exits.get(&ip) {
+                    block.exit = *exit;
+                    break;
+                }
+                if leaders.contains(&next) {
+                    block.exit = Exit::Fallthrough(next);
+                    break;
+                }
+                ip = next;
+            }
+            blocks.insert(start, block);
+        }
+
+        // the code is every address that's part of a reachable instruction, including ones that
+        // don't decode (yet, as the program might be about to write a valid instruction there)
+        let mut code = BTreeMap::new();
+        for (&ip, op_code) in &instructions {
+            let len = op_code.as_ref().map_or(1, OpCode::len);
+            for address in ip..ip + len {
+                code.entry(address).or_insert(ip);
+            }
+        }
+        let self_modifications = instructions
+            .iter()
+            .filter_map(|(&ip, op_code)| match op_code.as_ref()?.target()? {
+                IntCode::Position(address) => Some(SelfModification {
+                    ip,
+                    address,
+                    instruction: *code.get(&address)?,
+                }),
+                _ => None,
+            })
+            .collect();
+
+        Graph {
+            blocks,
+            self_modifications,
+        }
+    }
+
+    /// The start of every block that's called
+    pub fn functions(&self) -> BTreeSet<usize> {
+        self.blocks
+            .values()
+            .filter_map(|block| match block.exit {
+                Exit::Call { callee, .. } => Some(callee),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The graph in Graphviz's DOT language, e.g. for `dot -Tsvg`
+    pub fn to_dot(&self) -> String {
+        let modified = self
+            .self_modifications
+            .iter()
+            .map(|modification| modification.ip)
+            .collect::<BTreeSet<_>>();
+
+        let mut dot = String::from("digraph intcode {\n");
+        dot.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+        for block in self.blocks.values() {
+            let mut label = String::new();
+            for line in &block.lines {
+                let Kind::Instruction(op_code) = line.kind else {
+                    continue;
+                };
+                let marker = if modified.contains(&line.address) {
+                    " *"
+                } else {
+                    ""
+                };
+                label.push_str(&format!("{:>5}  {}{}\\l", line.address, op_code, marker));
+            }
+            if block.exit == Exit::Invalid {
+                label.push_str(&format!("{:>5}  invalid\\l", block.start));
+            }
+
+            let style = match block
+                .lines
+                .iter()
+                .any(|line| modified.contains(&line.address))
+            {
+                true => ", color=red",
+                false => "",
+            };
+            dot.push_str(&format!(
+                "    b{} [label=\"{}\"{}];\n",
+                block.start, label, style
+            ));
+        }
+        for block in self.blocks.values() {
+            for (successor, label) in block.exit.successors() {
+                let style = match label {
+                    "call" => ", style=bold",
+                    "returns to" => ", style=dashed",
+                    _ => "",
+                };
+                dot.push_str(&format!(
+                    "    b{} -> b{} [label=\"{}\"{}];\n",
+                    block.start, successor, label, style
+                ));
+            }
+        }
+        dot.push_str("}\n");
+
+        dot
+    }
+}
+
+/// Each block's listing and exit, followed by any self-modifying writes
+impl fmt::Display for Graph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let functions = self.functions();
+        for block in self.blocks.values() {
+            let kind = match functions.contains(&block.start) {
+                true => "function",
+                false => "block",
+            };
+            writeln!(f, "{} {}..{}:", kind, block.start, block.end())?;
+            for line in &block.lines {
+                writeln!(f, "{}", line)?;
+            }
+            writeln!(f, "       -> {}\n", block.exit)?;
+        }
+
+        if !self.self_modifications.is_empty() {
+            writeln!(f, "self-modifying writes:")?;
+        }
+        for modification in &self.self_modifications {
+            writeln!(
+                f,
+                "{:>5}  writes [{}] in the instruction at {}",
+                modification.ip, modification.address, modification.instruction
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,427 @@
+//! Translates a program into a closure per instruction, each specialised for its parameter modes,
+//! so running it doesn't decode anything or match on modes. Instructions are compiled the first
+//! time they run, and any that the program writes over are compiled again from the new ints.
+
+use super::error::{modes, AddressError, IntcodeError};
+use super::int_code::IntCode;
+use super::io::{IntRead, IntWrite};
+use super::memory::Memory;
+use super::op_code::OpCode;
+use super::program::StopReason;
+use super::Int;
+
+/// The longest an instruction can be, in ints
+const MAX_LEN: usize = 4;
+
+/// Where to go after an instruction
+enum Flow {
+    Continue(usize),
+    /// Halted, with the ip past the `Halt`
+    Halt(usize),
+    WaitingForInput,
+}
+
+type Op = Box<
+    dyn Fn(&mut State, &mut dyn IntRead, &mut dyn IntWrite) -> Result<Flow, IntcodeError>
+        + Send
+        + Sync,
+>;
+
+/// What the compiled instructions run against
+#[derive(Debug, Clone)]
+struct State {
+    rb: usize,
+    memory: Memory,
+    /// Addresses that have been written to and might hold a compiled instruction
+    stale: Vec<usize>,
+    /// Writes below this can change a compiled instruction, including the parameters of one that
+    /// starts near the end of the program's original ints
+    code_end: usize,
+}
+
+impl State {
+    #[inline]
+    fn write(&mut self, address: usize, value: Int) {
+        self.memory.set(address, value);
+        if address < self.code_end {
+            self.stale.push(address);
+        }
+    }
+}
+
+/// A parameter with its mode known when it's compiled
+trait Param: Copy + Send + Sync + 'static {
+    fn read(self, state: &State) -> Result<Int, AddressError>;
+
+    fn address(self, state: &State) -> Result<usize, AddressError>;
+}
+
+#[derive(Copy, Clone)]
+struct Position(usize);
+
+#[derive(Copy, Clone)]
+struct Immediate(Int);
+
+#[derive(Copy, Clone)]
+struct Relative(Int);
+
+impl Param for Position {
+    #[inline]
+    fn read(self, state: &State) -> Result<Int, AddressError> {
+        Ok(state.memory.get(self.0))
+    }
+
+    #[inline]
+    fn address(self, _: &State) -> Result<usize, AddressError> {
+        Ok(self.0)
+    }
+}
+
+impl Param for Immediate {
+    #[inline]
+    fn read(self, _: &State) -> Result<Int, AddressError> {
+        Ok(self.0)
+    }
+
+    #[inline]
+    fn address(self, _: &State) -> Result<usize, AddressError> {
+        Err(AddressError::WriteToImmediate)
+    }
+}
+
+impl Param for Relative {
+    #[inline]
+    fn read(self, state: &State) -> Result<Int, AddressError> {
+        Ok(state.memory.get(self.address(state)?))
+    }
+
+    #[inline]
+    fn address(self, state: &State) -> Result<usize, AddressError> {
+        IntCode::Relative(self.0).as_address(state.rb)
+    }
+}
+
+/// Evaluate `$body` with `$param` bound to the `Param` for `$code`'s mode
+macro_rules! with_param {
+    ($code:expr, |$param:ident| $body:expr) => {
+        match $code {
+            IntCode::Position(address) => {
+                let $param = Position(address);
+                $body
+            }
+            IntCode::Immediate(value) => {
+                let $param = Immediate(value);
+                $body
+            }
+            IntCode::Relative(offset) => {
+                let $param = Relative(offset);
+                $body
+            }
+        }
+    };
+}
+
+/// How a failing instruction reports which one it was
+#[derive(Copy, Clone)]
+struct Site {
+    ip: usize,
+    instruction: Int,
+}
+
+impl Site {
+    fn fail(self, e: AddressError) -> IntcodeError {
+        e.at(self.ip, self.instruction)
+    }
+
+    fn overflow(self) -> IntcodeError {
+        IntcodeError::Overflow {
+            ip: self.ip,
+            instruction: self.instruction,
+            modes: modes(self.instruction),
+        }
+    }
+}
+
+/// `target = f(lhs, rhs)`
+fn binary<A, B, T, F>(lhs: A, rhs: B, target: T, site: Site, next: usize, f: F) -> Op
+where
+    A: Param,
+    B: Param,
+    T: Param,
+    F: Fn(Int, Int) -> Option<Int> + Copy + Send + Sync + 'static,
+{
+    Box::new(move |state, _, _| {
+        let lhs = lhs.read(state).map_err(|e| site.fail(e))?;
+        let rhs = rhs.read(state).map_err(|e| site.fail(e))?;
+        let value = f(lhs, rhs).ok_or_else(|| site.overflow())?;
+        let address = target.address(state).map_err(|e| site.fail(e))?;
+        state.write(address, value);
+        Ok(Flow::Continue(next))
+    })
+}
+
+fn compile_binary<F>(
+    lhs: IntCode,
+    rhs: IntCode,
+    target: IntCode,
+    site: Site,
+    next: usize,
+    f: F,
+) -> Op
+where
+    F: Fn(Int, Int) -> Option<Int> + Copy + Send + Sync + 'static,
+{
+    with_param!(lhs, |lhs| {
+        with_param!(rhs, |rhs| {
+            with_param!(target, |target| binary(lhs, rhs, target, site, next, f))
+        })
+    })
+}
+
+fn jump<P: Param, D: Param>(test: P, destination: D, if_true: bool, site: Site, next: usize) -> Op {
+    Box::new(move |state, _, _| {
+        if (test.read(state).map_err(|e| site.fail(e))? != 0) != if_true {
+            return Ok(Flow::Continue(next));
+        }
+
+        match destination.read(state).map_err(|e| site.fail(e))? {
+            destination if destination < 0 => {
+                Err(site.fail(AddressError::NegativeAddress(destination)))
+            }
+            destination => Ok(Flow::Continue(destination as usize)),
+        }
+    })
+}
+
+fn compile(op_code: OpCode, site: Site, checked: bool) -> Op {
+    let next = site.ip + op_code.len();
+    match op_code {
+        OpCode::Add { lhs, rhs, target } => match checked {
+            true => compile_binary(lhs, rhs, target, site, next, Int::checked_add),
+            false => compile_binary(lhs, rhs, target, site, next, |a, b| Some(a.wrapping_add(b))),
+        },
+        OpCode::Mult { lhs, rhs, target } => match checked {
+            true => compile_binary(lhs, rhs, target, site, next, Int::checked_mul),
+            false => compile_binary(lhs, rhs, target, site, next, |a, b| Some(a.wrapping_mul(b))),
+        },
+        OpCode::LessThan { lhs, rhs, target } => {
+            compile_binary(lhs, rhs, target, site, next, |a, b| Some((a < b) as Int))
+        }
+        OpCode::Equals { lhs, rhs, target } => {
+            compile_binary(lhs, rhs, target, site, next, |a, b| Some((a == b) as Int))
+        }
+        OpCode::Input { target } => with_param!(target, |target| {
+            let op: Op = Box::new(move |state, input, _| match input.int_read() {
+                Some(value) => {
+                    let address = target.address(state).map_err(|e| site.fail(e))?;
+                    state.write(address, value);
+                    Ok(Flow::Continue(next))
+                }
+                None => Ok(Flow::WaitingForInput),
+            });
+            op
+        }),
+        OpCode::Output { target } => with_param!(target, |target| {
+            let op: Op = Box::new(move |state, _, output| {
+                output.int_write(target.read(state).map_err(|e| site.fail(e))?);
+                Ok(Flow::Continue(next))
+            });
+            op
+        }),
+        OpCode::JumpIfTrue { test, destination } => with_param!(test, |test| {
+            with_param!(destination, |to| jump(test, to, true, site, next))
+        }),
+        OpCode::JumpIfFalse { test, destination } => with_param!(test, |test| {
+            with_param!(destination, |to| jump(test, to, false, site, next))
+        }),
+        OpCode::AdjustRelativeBase { amount } => with_param!(amount, |amount| {
+            let op: Op = Box::new(move |state, _, _| {
+                let amount = amount.read(state).map_err(|e| site.fail(e))?;
+                state.rb = match (state.rb as Int).checked_add(amount) {
+                    Some(rb) => rb as usize,
+                    None if checked => return Err(site.overflow()),
+                    None => (state.rb as Int).wrapping_add(amount) as usize,
+                };
+                Ok(Flow::Continue(next))
+            });
+            op
+        }),
+        OpCode::Halt => Box::new(move |_, _, _| Ok(Flow::Halt(next))),
+    }
+}
+
+/// A program that runs as compiled closures, with the same interface as `Program`
+pub struct Compiled {
+    ip: usize,
+    state: State,
+    /// The compiled instruction at each address of the program's original ints, instructions
+    /// past them are compiled every time they run
+    ops: Vec<Option<Op>>,
+    checked: bool,
+    /// How many compiled instructions the program has written over
+    invalidated: usize,
+}
+
+impl Compiled {
+    pub fn new(ints: Vec<Int>) -> Compiled {
+        let len = ints.len();
+        Compiled {
+            ip: 0,
+            state: State {
+                rb: 0,
+                memory: Memory::new(ints),
+                stale: vec![],
+                code_end: len + MAX_LEN - 1,
+            },
+            ops: (0..len).map(|_| None).collect(),
+            checked: false,
+            invalidated: 0,
+        }
+    }
+
+    /// See `Program::with_checked_arithmetic`
+    pub fn with_checked_arithmetic(mut self, checked: bool) -> Compiled {
+        self.checked = checked;
+        self.ops.iter_mut().for_each(|op| *op = None);
+        self
+    }
+
+    /// Instruction Pointer
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+
+    /// Relative Base
+    pub fn rb(&self) -> usize {
+        self.state.rb
+    }
+
+    pub fn memory(&self) -> &Memory {
+        &self.state.memory
+    }
+
+    /// The dense region of the program's memory, see `Memory`
+    pub fn get_memory(&self) -> Vec<Int> {
+        self.state.memory.dense().to_vec()
+    }
+
+    /// How many compiled instructions the program has written over (and so had to be compiled
+    /// again), which is 0 unless it modifies its own code
+    pub fn invalidated(&self) -> usize {
+        self.invalidated
+    }
+
+    fn compile(&self, ip: usize) -> Result<Op, IntcodeError> {
+        let mut next = ip;
+        let op_code = OpCode::next(&mut next, &self.state.memory)?;
+        let site = Site {
+            ip,
+            instruction: self.state.memory.get(ip),
+        };
+
+        Ok(compile(op_code, site, self.checked))
+    }
+
+    /// Execute a single instruction, see `Program::step`
+    #[inline]
+    pub fn step<R, O>(
+        &mut self,
+        input: &mut R,
+        output: &mut O,
+    ) -> Result<Option<StopReason>, IntcodeError>
+    where
+        R: IntRead,
+        O: IntWrite,
+    {
+        let ip = self.ip;
+        let flow = match self.ops.get(ip) {
+            Some(Some(op)) => op(&mut self.state, input, output)?,
+            Some(None) => {
+                let op = self.compile(ip)?;
+                self.ops[ip].insert(op)(&mut self.state, input, output)?
+            }
+            None => self.compile(ip)?(&mut self.state, input, output)?,
+        };
+
+        // forget the compiled instructions the program wrote over
+        for address in self.state.stale.drain(..) {
+            let start = address.saturating_sub(MAX_LEN - 1);
+            for op in self.ops.iter_mut().take(address + 1).skip(start) {
+                self.invalidated += op.take().is_some() as usize;
+            }
+        }
+
+        match flow {
+            Flow::Continue(next) => {
+                self.ip = next;
+                Ok(None)
+            }
+            Flow::Halt(next) => {
+                self.ip = next;
+                Ok(Some(StopReason::Halt))
+            }
+            Flow::WaitingForInput => Ok(Some(StopReason::WaitingForInput)),
+        }
+    }
+
+    pub fn run<R, O>(&mut self, input: R, output: O) -> Result<StopReason, IntcodeError>
+    where
+        R: IntRead,
+        O: IntWrite,
+    {
+        self.run_with_budget(input, output, u64::MAX)
+    }
+
+    pub fn run_no_io(&mut self) -> Result<StopReason, IntcodeError> {
+        self.run(None, &mut Vec::new())
+    }
+
+    /// See `Program::run_with_budget`
+    pub fn run_with_budget<R, O>(
+        &mut self,
+        mut input: R,
+        mut output: O,
+        budget: u64,
+    ) -> Result<StopReason, IntcodeError>
+    where
+        R: IntRead,
+        O: IntWrite,
+    {
+        for _ in 0..budget {
+            if let Some(reason) = self.step(&mut input, &mut output)? {
+                return Ok(reason);
+            }
+        }
+
+        Ok(StopReason::BudgetExhausted)
+    }
+
+    /// See `Program::run_until_output`
+    pub fn run_until_output<R: IntRead>(
+        &mut self,
+        mut input: R,
+    ) -> Result<StopReason, IntcodeError> {
+        let mut output = None;
+        loop {
+            if let Some(reason) = self.step(&mut input, &mut &mut output)? {
+                return Ok(reason);
+            }
+            if let Some(value) = output {
+                return Ok(StopReason::Output(value));
+            }
+        }
+    }
+}
+
+/// Clones the machine's state, its instructions are compiled again as they run
+impl Clone for Compiled {
+    fn clone(&self) -> Compiled {
+        Compiled {
+            ip: self.ip,
+            state: self.state.clone(),
+            ops: (0..self.ops.len()).map(|_| None).collect(),
+            checked: self.checked,
+            invalidated: self.invalidated,
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Which addresses a program executed and what it wrote where, see `Program::with_coverage`
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use super::disasm;
+use super::Int;
+
+/// Executed addresses below this are kept in a bitmap that grows to fit them, the rest in a set
+const BITMAP_LEN: usize = 1 << 20;
+
+/// A value written by the instruction at `ip`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryWrite<W = Int> {
+    pub ip: usize,
+    pub address: usize,
+    pub value: W,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverage<W = Int> {
+    /// A bit per address, set once an instruction starting there has executed
+    executed: Vec<u64>,
+    /// Executed addresses past the bitmap, e.g. code a program wrote far out in memory
+    sparse: BTreeSet<usize>,
+    /// The instruction that's executing, which any writes are logged against
+    ip: usize,
+    writes: Vec<MemoryWrite<W>>,
+}
+
+impl<W> Default for Coverage<W> {
+    fn default() -> Coverage<W> {
+        Coverage {
+            executed: vec![],
+            sparse: BTreeSet::new(),
+            ip: 0,
+            writes: vec![],
+        }
+    }
+}
+
+impl<W> Coverage<W> {
+    pub fn new() -> Coverage<W> {
+        Coverage::default()
+    }
+
+    pub(crate) fn execute(&mut self, ip: usize) {
+        self.ip = ip;
+        if ip >= BITMAP_LEN {
+            self.sparse.insert(ip);
+            return;
+        }
+
+        let word = ip / 64;
+        if word >= self.executed.len() {
+            self.executed.resize(word + 1, 0);
+        }
+        self.executed[word] |= 1 << (ip % 64);
+    }
+
+    pub(crate) fn write(&mut self, address: usize, value: W) {
+        self.writes.push(MemoryWrite {
+            ip: self.ip,
+            address,
+            value,
+        });
+    }
+
+    /// Whether an instruction starting at `address` has executed
+    pub fn is_executed(&self, address: usize) -> bool {
+        if address >= BITMAP_LEN {
+            return self.sparse.contains(&address);
+        }
+
+        self.executed
+            .get(address / 64)
+            .is_some_and(|word| word & (1 << (address % 64)) != 0)
+    }
+
+    /// The start of every instruction that has executed, in order
+    pub fn executed(&self) -> impl Iterator<Item = usize> + '_ {
+        self.executed
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &word)| {
+                (0..64)
+                    .filter(move |bit| word & (1 << bit) != 0)
+                    .map(move |bit| i * 64 + bit)
+            })
+            .chain(self.sparse.iter().copied())
+    }
+
+    /// Every write in the order it happened
+    pub fn writes(&self) -> &[MemoryWrite<W>] {
+        &self.writes
+    }
+
+    /// A disassembly listing of `ints` (usually the program as it was loaded), with a `>` in front
+    /// of every line that executed. Data lines are marked too if the program wrote instructions
+    /// into them and ran those.
+    pub fn listing(&self, ints: &[Int]) -> String {
+        let mut listing = String::new();
+        for line in disasm::disassemble(ints) {
+            let range = line.address..line.address + line.ints.len();
+            let marker = match range.clone().any(|address| self.is_executed(address)) {
+                true => '>',
+                false => ' ',
+            };
+            writeln!(listing, "{} {}", marker, line).unwrap();
+        }
+
+        listing
+    }
+}
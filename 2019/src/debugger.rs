@@ -0,0 +1,325 @@
+//! A stepping debugger for intcode programs. Either run it through `intcode debug <path>`, or attach
+//! it to a program from a solution with `debugger::attach(&mut program)?`.
+
+use std::collections::{BTreeSet, VecDeque};
+use std::io::{self, BufRead, Write};
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
+use super::disasm::{self, Line};
+use super::error::IntcodeError;
+use super::program::{Program, StopReason};
+use super::Int;
+
+/// How many ints `list` and `mem` show by default
+const WINDOW: usize = 16;
+/// The most ints `list` and `mem` show, however many are asked for
+const MAX_WINDOW: usize = 4096;
+
+const HELP: &str = "\
+commands:
+  s, step [n]          execute n instructions (default 1)
+  c, continue          run until a breakpoint, a watchpoint or the program stops
+  b, break <ip>        set a breakpoint (no address lists the breakpoints)
+  db <ip>              delete a breakpoint
+  w, watch <address>   stop when the value at an address changes (no address lists them)
+  dw <address>         delete a watchpoint
+  r, regs              show the registers
+  l, list [ip] [n]     disassemble n ints from ip (default: the current ip)
+  m, mem <address> [n] show a window of memory with its disassembly
+  i, input <ints>      queue comma or space separated ints as input
+  ia <text>            queue a line of ASCII as input
+  h, help              show this help
+  q, quit              stop debugging";
+
+/// Why the debugger gave control back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// A single step finished without anything of note happening
+    Stepped,
+    Breakpoint(usize),
+    Watchpoint {
+        address: usize,
+        old: Int,
+        new: Int,
+    },
+    Stopped(StopReason),
+}
+
+pub struct Debugger<'a> {
+    program: &'a mut Program,
+    breakpoints: BTreeSet<usize>,
+    watchpoints: BTreeSet<usize>,
+    /// Input that's been injected but not read by the program yet
+    input: VecDeque<Int>,
+    /// Output the program has written since it was last taken
+    output: Vec<Int>,
+    /// The ip moves past a halt instruction, so stepping again would run whatever follows it
+    halted: bool,
+}
+
+impl<'a> Debugger<'a> {
+    pub fn new(program: &'a mut Program) -> Debugger<'a> {
+        Debugger {
+            program,
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeSet::new(),
+            input: VecDeque::new(),
+            output: vec![],
+            halted: false,
+        }
+    }
+
+    pub fn program(&self) -> &Program {
+        self.program
+    }
+
+    pub fn add_breakpoint(&mut self, ip: usize) {
+        self.breakpoints.insert(ip);
+    }
+
+    pub fn remove_breakpoint(&mut self, ip: usize) -> bool {
+        self.breakpoints.remove(&ip)
+    }
+
+    pub fn add_watchpoint(&mut self, address: usize) {
+        self.watchpoints.insert(address);
+    }
+
+    pub fn remove_watchpoint(&mut self, address: usize) -> bool {
+        self.watchpoints.remove(&address)
+    }
+
+    pub fn push_input(&mut self, values: impl IntoIterator<Item = Int>) {
+        self.input.extend(values);
+    }
+
+    /// Everything the program has output since this was last called
+    pub fn take_output(&mut self) -> Vec<Int> {
+        std::mem::take(&mut self.output)
+    }
+
+    /// Execute a single instruction
+    pub fn step(&mut self) -> Result<Event, IntcodeError> {
+        if self.halted {
+            return Ok(Event::Stopped(StopReason::Halt));
+        }
+
+        let memory = self.program.memory();
+        let watched = self
+            .watchpoints
+            .iter()
+            .map(|address| (*address, memory.get(*address)))
+            .collect::<Vec<_>>();
+
+        let mut input = &mut self.input;
+        let mut output = &mut self.output;
+        if let Some(reason) = self.program.step(&mut input, &mut output)? {
+            self.halted = reason == StopReason::Halt;
+            return Ok(Event::Stopped(reason));
+        }
+
+        let memory = self.program.memory();
+        for (address, old) in watched {
+            let new = memory.get(address);
+            if new != old {
+                return Ok(Event::Watchpoint { address, old, new });
+            }
+        }
+
+        match self.program.ip() {
+            ip if self.breakpoints.contains(&ip) => Ok(Event::Breakpoint(ip)),
+            _ => Ok(Event::Stepped),
+        }
+    }
+
+    /// Run until a breakpoint or watchpoint is hit, or the program stops
+    pub fn resume(&mut self) -> Result<Event, IntcodeError> {
+        loop {
+            match self.step()? {
+                Event::Stepped => continue,
+                event => return Ok(event),
+            }
+        }
+    }
+
+    /// Disassemble `len` ints of memory (at most `MAX_WINDOW`) starting at `start`
+    pub fn disassemble(&self, start: usize, len: usize) -> Vec<Line> {
+        let memory = self.program.memory();
+        let window = (start..start.saturating_add(len.min(MAX_WINDOW)))
+            .map(|address| memory.get(address))
+            .collect::<Vec<_>>();
+
+        disasm::disassemble(&window)
+            .into_iter()
+            .map(|mut line| {
+                line.address += start;
+                line
+            })
+            .collect()
+    }
+
+    pub fn registers(&self) -> String {
+        format!("ip {}  rb {}", self.program.ip(), self.program.rb())
+    }
+
+    fn print_location(&self, out: &mut impl Write) -> io::Result<()> {
+        match self.disassemble(self.program.ip(), 4).first() {
+            Some(line) => writeln!(out, "=> {}", line),
+            None => Ok(()),
+        }
+    }
+
+    fn print_lines(&self, out: &mut impl Write, start: usize, len: usize) -> io::Result<()> {
+        for line in self.disassemble(start, len) {
+            let marker = match line.address {
+                address if address == self.program.ip() => "=>",
+                address if self.breakpoints.contains(&address) => " *",
+                _ => "  ",
+            };
+            writeln!(out, "{} {}", marker, line)?;
+        }
+
+        Ok(())
+    }
+
+    fn print_event(&mut self, out: &mut impl Write, event: Event) -> io::Result<()> {
+        let output = self.take_output();
+        if !output.is_empty() {
+            writeln!(out, "output: {}", output.iter().join(","))?;
+        }
+
+        match event {
+            Event::Stepped => {}
+            Event::Breakpoint(ip) => writeln!(out, "breakpoint at {}", ip)?,
+            Event::Watchpoint { address, old, new } => {
+                writeln!(out, "watchpoint: [{}] {} -> {}", address, old, new)?
+            }
+            Event::Stopped(StopReason::Halt) => return writeln!(out, "halted"),
+            Event::Stopped(StopReason::WaitingForInput) => {
+                writeln!(out, "waiting for input (queue some with `input`)")?
+            }
+            Event::Stopped(reason) => writeln!(out, "stopped: {:?}", reason)?,
+        }
+
+        self.print_location(out)
+    }
+
+    /// Run a single REPL command, returns false once the user quits
+    fn command(&mut self, line: &str, out: &mut impl Write) -> Result<bool> {
+        let (command, rest) = line
+            .trim()
+            .split_once(char::is_whitespace)
+            .unwrap_or((line.trim(), ""));
+        let args = rest
+            .split([',', ' '])
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>();
+        let arg = |i: usize| -> Result<Option<usize>> {
+            args.get(i)
+                .map(|s| s.parse().map_err(|_| anyhow!("'{}' isn't an address", s)))
+                .transpose()
+        };
+        let required = |i: usize| -> Result<usize> {
+            arg(i)?.ok_or_else(|| anyhow!("'{}' needs an address", command))
+        };
+        let window = |i: usize| -> Result<usize> { Ok(arg(i)?.unwrap_or(WINDOW).min(MAX_WINDOW)) };
+
+        match command {
+            "" => {}
+            "s" | "step" => {
+                let mut event = Event::Stepped;
+                for _ in 0..arg(0)?.unwrap_or(1) {
+                    event = self.step()?;
+                    if event != Event::Stepped {
+                        break;
+                    }
+                }
+                self.print_event(out, event)?;
+            }
+            "c" | "continue" => {
+                let event = self.resume()?;
+                self.print_event(out, event)?;
+            }
+            "b" | "break" if args.is_empty() => {
+                writeln!(out, "breakpoints: {}", self.breakpoints.iter().join(", "))?
+            }
+            "b" | "break" => self.add_breakpoint(required(0)?),
+            "db" => {
+                if !self.remove_breakpoint(required(0)?) {
+                    writeln!(out, "no breakpoint at {}", required(0)?)?;
+                }
+            }
+            "w" | "watch" if args.is_empty() => {
+                writeln!(out, "watchpoints: {}", self.watchpoints.iter().join(", "))?
+            }
+            "w" | "watch" => self.add_watchpoint(required(0)?),
+            "dw" => {
+                if !self.remove_watchpoint(required(0)?) {
+                    writeln!(out, "no watchpoint at {}", required(0)?)?;
+                }
+            }
+            "r" | "regs" => writeln!(out, "{}", self.registers())?,
+            "l" | "list" => {
+                let start = arg(0)?.unwrap_or_else(|| self.program.ip());
+                self.print_lines(out, start, window(1)?)?;
+            }
+            "m" | "mem" => {
+                let start = required(0)?;
+                let len = window(1)?;
+                let memory = self.program.memory();
+                for chunk in &(start..start.saturating_add(len)).chunks(8) {
+                    let chunk = chunk.collect::<Vec<_>>();
+                    let values = chunk.iter().map(|a| format!("{:>6}", memory.get(*a)));
+                    writeln!(out, "{:>5}: {}", chunk[0], values.format(" "))?;
+                }
+                self.print_lines(out, start, len)?;
+            }
+            "i" | "input" => {
+                let values = args
+                    .iter()
+                    .map(|s| s.parse::<Int>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| anyhow!("invalid input: {}", e))?;
+                self.push_input(values);
+            }
+            "ia" => {
+                self.push_input(rest.bytes().map(Int::from));
+                self.push_input(Some(Int::from(b'\n')));
+            }
+            "h" | "help" => writeln!(out, "{}", HELP)?,
+            "q" | "quit" => return Ok(false),
+            _ => writeln!(out, "unknown command '{}', try `help`", command)?,
+        }
+
+        Ok(true)
+    }
+
+    /// Read commands from `input` until it ends or the user quits
+    pub fn repl(&mut self, input: impl BufRead, mut out: impl Write) -> Result<()> {
+        writeln!(out, "{}", self.registers())?;
+        self.print_location(&mut out)?;
+        write!(out, "(intcode) ")?;
+        out.flush()?;
+
+        for line in input.lines() {
+            match self.command(&line?, &mut out) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => writeln!(out, "error: {}", e)?,
+            }
+            write!(out, "(intcode) ")?;
+            out.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Debug a program over stdin and stdout, e.g. from a solution that's misbehaving
+pub fn attach(program: &mut Program) -> Result<()> {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    Debugger::new(program).repl(stdin.lock(), stdout.lock())
+}
@@ -0,0 +1,109 @@
+//! The memory that differs between two states of a program, e.g. before and after it ran, shown
+//! under the lines of the disassembly that they fall in.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use super::disasm::{self, Line};
+use super::snapshot::Snapshot;
+use super::Int;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub address: usize,
+    pub before: Int,
+    pub after: Int,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {} -> {}", self.address, self.before, self.after)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diff {
+    /// Every address that differs, in order
+    pub changes: Vec<Change>,
+    /// The disassembly of the memory before, to annotate the changes with
+    lines: Vec<Line>,
+}
+
+impl Diff {
+    /// Compare two `Program::get_memory()`s, where the shorter one reads as 0 past its end
+    pub fn new(before: &[Int], after: &[Int]) -> Diff {
+        let get = |ints: &[Int], address| ints.get(address).copied().unwrap_or(0);
+        let changes = (0..before.len().max(after.len()))
+            .map(|address| Change {
+                address,
+                before: get(before, address),
+                after: get(after, address),
+            })
+            .filter(|change| change.before != change.after)
+            .collect();
+
+        Diff {
+            changes,
+            lines: disasm::disassemble(before),
+        }
+    }
+
+    /// Compare the memory of two snapshots, including anything stored outside the dense region
+    pub fn between(before: &Snapshot, after: &Snapshot) -> Diff {
+        let (before, after) = (&before.memory, &after.memory);
+        let mut addresses =
+            (0..before.dense().len().max(after.dense().len())).collect::<BTreeSet<_>>();
+        addresses.extend(
+            before
+                .sparse()
+                .chain(after.sparse())
+                .map(|(address, _)| address),
+        );
+
+        let changes = addresses
+            .into_iter()
+            .map(|address| Change {
+                address,
+                before: before.get(address),
+                after: after.get(address),
+            })
+            .filter(|change| change.before != change.after)
+            .collect();
+
+        Diff {
+            changes,
+            lines: disasm::disassemble(before.dense()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Each line of the disassembly with a change in it, followed by its changes
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut changes = self.changes.iter().peekable();
+        for line in &self.lines {
+            let end = line.address + line.ints.len();
+            if changes.peek().is_none_or(|change| change.address >= end) {
+                continue;
+            }
+
+            writeln!(f, "{}", line)?;
+            while let Some(change) = changes.next_if(|change| change.address < end) {
+                writeln!(f, "         {}", change)?;
+            }
+        }
+
+        if changes.peek().is_some() {
+            writeln!(f, "past the end of the program:")?;
+        }
+        for change in changes {
+            writeln!(f, "         {}", change)?;
+        }
+
+        Ok(())
+    }
+}
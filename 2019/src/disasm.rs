@@ -0,0 +1,77 @@
+use std::fmt;
+
+use itertools::Itertools;
+
+use super::op_code::OpCode;
+use super::Int;
+
+/// How many data values are grouped onto a single line
+const DATA_PER_LINE: usize = 8;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Kind {
+    Instruction(OpCode),
+    /// Values that don't decode to an instruction
+    Data,
+}
+
+/// A single line of a disassembly listing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub address: usize,
+    /// The raw ints the line was decoded from
+    pub ints: Vec<Int>,
+    pub kind: Kind,
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let raw = self.ints.iter().join(",");
+        write!(f, "{:>5}  {:<24}  ", self.address, raw)?;
+        match self.kind {
+            Kind::Instruction(op_code) => write!(f, "{}", op_code),
+            Kind::Data => write!(f, "data {}", self.ints.iter().join(", ")),
+        }
+    }
+}
+
+/// Disassemble a program with a linear sweep: anything that decodes is treated as an instruction,
+/// and everything else is grouped into data lines.
+/// Intcode freely mixes code and data, so some data (e.g. a stray `99`) will show up as instructions.
+pub fn disassemble(ints: &[Int]) -> Vec<Line> {
+    let mut lines: Vec<Line> = vec![];
+    let mut ip = 0;
+    while ip < ints.len() {
+        let address = ip;
+        match OpCode::next(&mut ip, ints) {
+            Ok(op_code) => lines.push(Line {
+                address,
+                ints: ints[address..ip].to_vec(),
+                kind: Kind::Instruction(op_code),
+            }),
+            Err(_) => {
+                ip = address + 1;
+                match lines.last_mut() {
+                    Some(line) if line.kind == Kind::Data && line.ints.len() < DATA_PER_LINE => {
+                        line.ints.push(ints[address])
+                    }
+                    _ => lines.push(Line {
+                        address,
+                        ints: vec![ints[address]],
+                        kind: Kind::Data,
+                    }),
+                }
+            }
+        }
+    }
+
+    lines
+}
+
+/// A printable listing of the whole program
+pub fn listing(ints: &[Int]) -> String {
+    disassemble(ints)
+        .iter()
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
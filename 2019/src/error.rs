@@ -0,0 +1,152 @@
+use std::error::Error;
+use std::fmt;
+
+use super::Int;
+
+/// The parameter modes of an instruction, in parameter order
+pub fn modes(instruction: Int) -> [Int; 3] {
+    // `abs` would overflow for `Int::MIN`, and the digits that are left fit in an `Int`
+    let modes = (instruction.unsigned_abs() / 100) as Int;
+    [modes % 10, modes / 10 % 10, modes / 100 % 10]
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntcodeError {
+    /// The instruction's opcode isn't one that's known
+    InvalidOpcode {
+        ip: usize,
+        instruction: Int,
+        modes: [Int; 3],
+    },
+    /// A parameter's mode is neither position (0), immediate (1) nor relative (2)
+    InvalidMode {
+        ip: usize,
+        instruction: Int,
+        modes: [Int; 3],
+        /// Index of the offending parameter
+        parameter: usize,
+    },
+    /// An instruction tried to write to a parameter in immediate mode
+    WriteToImmediate {
+        ip: usize,
+        instruction: Int,
+        modes: [Int; 3],
+    },
+    /// A parameter resolved to an address below zero
+    NegativeAddress {
+        ip: usize,
+        instruction: Int,
+        modes: [Int; 3],
+        address: Int,
+    },
+    /// A parameter resolved to an address outside of the memory.
+    /// Only raised when decoding fixed-size memory (e.g. a `&[Int]`), a `Memory` grows instead.
+    MemoryOutOfRange {
+        ip: usize,
+        instruction: Int,
+        modes: [Int; 3],
+        address: usize,
+    },
+    /// An `Add` or `Mult` overflowed the word size (only in checked mode, see
+    /// `Program::with_checked_arithmetic`)
+    Overflow {
+        ip: usize,
+        instruction: Int,
+        modes: [Int; 3],
+    },
+}
+
+impl IntcodeError {
+    /// Position of the instruction that failed
+    pub fn ip(&self) -> usize {
+        match self {
+            IntcodeError::InvalidOpcode { ip, .. }
+            | IntcodeError::InvalidMode { ip, .. }
+            | IntcodeError::WriteToImmediate { ip, .. }
+            | IntcodeError::NegativeAddress { ip, .. }
+            | IntcodeError::MemoryOutOfRange { ip, .. }
+            | IntcodeError::Overflow { ip, .. } => *ip,
+        }
+    }
+
+    /// The raw instruction that failed
+    pub fn instruction(&self) -> Int {
+        match self {
+            IntcodeError::InvalidOpcode { instruction, .. }
+            | IntcodeError::InvalidMode { instruction, .. }
+            | IntcodeError::WriteToImmediate { instruction, .. }
+            | IntcodeError::NegativeAddress { instruction, .. }
+            | IntcodeError::MemoryOutOfRange { instruction, .. }
+            | IntcodeError::Overflow { instruction, .. } => *instruction,
+        }
+    }
+}
+
+impl fmt::Display for IntcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntcodeError::InvalidOpcode { instruction, .. } => {
+                write!(f, "invalid opcode {}", instruction % 100)?
+            }
+            IntcodeError::InvalidMode {
+                modes, parameter, ..
+            } => write!(
+                f,
+                "invalid mode {} for parameter {}",
+                modes[*parameter], parameter
+            )?,
+            IntcodeError::WriteToImmediate { .. } => write!(f, "write to an immediate parameter")?,
+            IntcodeError::NegativeAddress { address, .. } => {
+                write!(f, "negative address {}", address)?
+            }
+            IntcodeError::MemoryOutOfRange { address, .. } => {
+                write!(f, "address {} is out of range", address)?
+            }
+            IntcodeError::Overflow { .. } => write!(f, "arithmetic overflow")?,
+        }
+
+        write!(
+            f,
+            " at ip {} (instruction {}, modes {:?})",
+            self.ip(),
+            self.instruction(),
+            modes(self.instruction())
+        )
+    }
+}
+
+impl Error for IntcodeError {}
+
+/// Resolving a parameter to a value or an address failed.
+/// Doesn't know which instruction it happened in, see `AddressError::at`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AddressError {
+    WriteToImmediate,
+    NegativeAddress(Int),
+    MemoryOutOfRange(usize),
+}
+
+impl AddressError {
+    pub fn at(self, ip: usize, instruction: Int) -> IntcodeError {
+        let modes = modes(instruction);
+        match self {
+            AddressError::WriteToImmediate => IntcodeError::WriteToImmediate {
+                ip,
+                instruction,
+                modes,
+            },
+            AddressError::NegativeAddress(address) => IntcodeError::NegativeAddress {
+                ip,
+                instruction,
+                modes,
+                address,
+            },
+            AddressError::MemoryOutOfRange(address) => IntcodeError::MemoryOutOfRange {
+                ip,
+                instruction,
+                modes,
+                address,
+            },
+        }
+    }
+}
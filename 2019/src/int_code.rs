@@ -1,26 +1,59 @@
+use std::fmt;
+
+use super::error::AddressError;
+use super::memory::MemoryRead;
+use super::word::Word;
 use super::Int;
 
-#[derive(Debug, Copy, Clone)]
-pub enum IntCode {
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IntCode<W = Int> {
     Position(usize),
-    Immediate(Int),
-    Relative(Int),
+    Immediate(W),
+    Relative(W),
 }
 
-impl IntCode {
-    pub fn as_int(self, memory: &[Int], rb: usize) -> Int {
+impl<W: Word> IntCode<W> {
+    pub fn as_int<M: MemoryRead<W> + ?Sized>(
+        &self,
+        memory: &M,
+        rb: usize,
+    ) -> Result<W, AddressError> {
+        match self {
+            IntCode::Immediate(x) => Ok(x.clone()),
+            _ => {
+                let address = self.as_address(rb)?;
+                memory
+                    .int_at(address)
+                    .ok_or(AddressError::MemoryOutOfRange(address))
+            }
+        }
+    }
+
+    pub fn as_address(&self, rb: usize) -> Result<usize, AddressError> {
         match self {
-            IntCode::Immediate(x) => x,
-            IntCode::Position(i) => memory[i],
-            IntCode::Relative(offset) => memory[((rb as Int) + offset) as usize],
+            IntCode::Position(i) => Ok(*i),
+            IntCode::Relative(offset) => {
+                match offset.to_int().and_then(|o| (rb as Int).checked_add(o)) {
+                    Some(address) if address < 0 => Err(AddressError::NegativeAddress(address)),
+                    Some(address) => Ok(address as usize),
+                    // an offset that doesn't even fit in an `Int` is far outside of any memory
+                    None if offset.is_negative() => Err(AddressError::NegativeAddress(Int::MIN)),
+                    None => Err(AddressError::MemoryOutOfRange(usize::MAX)),
+                }
+            }
+            IntCode::Immediate(_) => Err(AddressError::WriteToImmediate),
         }
     }
+}
 
-    pub fn as_address(self, rb: usize) -> Option<usize> {
+/// Renders the parameter in its mode: `[pos]`, `#imm` or `rb+off`
+impl<W: Word> fmt::Display for IntCode<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            IntCode::Position(i) => Some(i),
-            IntCode::Relative(offset) => Some(((rb as Int) + offset) as usize),
-            IntCode::Immediate(_) => None,
+            IntCode::Position(address) => write!(f, "[{}]", address),
+            IntCode::Immediate(value) => write!(f, "#{}", value),
+            IntCode::Relative(offset) if offset.is_negative() => write!(f, "rb{}", offset),
+            IntCode::Relative(offset) => write!(f, "rb+{}", offset),
         }
     }
 }
@@ -0,0 +1,136 @@
+//! I/O for programs that talk in ASCII text
+
+use std::collections::VecDeque;
+
+use super::{IntRead, IntWrite};
+use crate::Int;
+
+/// Queued text for a program to read
+#[derive(Debug, Clone, Default)]
+pub struct AsciiInput {
+    queue: VecDeque<Int>,
+}
+
+impl AsciiInput {
+    pub fn new() -> AsciiInput {
+        AsciiInput::default()
+    }
+
+    pub fn push_str(&mut self, text: &str) {
+        self.queue.extend(text.bytes().map(Int::from));
+    }
+
+    /// Queue `line` followed by a newline, which is how most programs expect commands
+    pub fn push_line(&mut self, line: &str) {
+        self.push_str(line);
+        self.queue.push_back(Int::from(b'\n'));
+    }
+
+    /// Queue raw ints, e.g. ones that were saved in a `Snapshot`
+    pub fn push_ints(&mut self, ints: impl IntoIterator<Item = Int>) {
+        self.queue.extend(ints);
+    }
+
+    /// What hasn't been read yet
+    pub fn pending(&self) -> impl Iterator<Item = Int> + '_ {
+        self.queue.iter().copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.queue.clear();
+    }
+}
+
+impl From<&str> for AsciiInput {
+    fn from(text: &str) -> AsciiInput {
+        let mut input = AsciiInput::new();
+        input.push_str(text);
+        input
+    }
+}
+
+impl<'a> FromIterator<&'a str> for AsciiInput {
+    /// One line per item
+    fn from_iter<I: IntoIterator<Item = &'a str>>(lines: I) -> AsciiInput {
+        let mut input = AsciiInput::new();
+        for line in lines {
+            input.push_line(line);
+        }
+        input
+    }
+}
+
+impl IntRead for AsciiInput {
+    fn int_read(&mut self) -> Option<Int> {
+        self.queue.pop_front()
+    }
+}
+
+impl IntRead for &mut AsciiInput {
+    fn int_read(&mut self) -> Option<Int> {
+        self.queue.pop_front()
+    }
+}
+
+/// Collects the text a program writes. Ints that aren't ASCII (usually the final answer) are kept
+/// separately.
+#[derive(Debug, Clone, Default)]
+pub struct AsciiOutput {
+    text: String,
+    values: Vec<Int>,
+}
+
+impl AsciiOutput {
+    pub fn new() -> AsciiOutput {
+        AsciiOutput::default()
+    }
+
+    /// The text written since it was last taken
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn take_text(&mut self) -> String {
+        std::mem::take(&mut self.text)
+    }
+
+    /// Take every complete line written so far, leaving a partial last line (e.g. a prompt)
+    pub fn take_lines(&mut self) -> Vec<String> {
+        let end = match self.text.rfind('\n') {
+            Some(end) => end,
+            None => return vec![],
+        };
+        let rest = self.text.split_off(end + 1);
+        let text = std::mem::replace(&mut self.text, rest);
+
+        text.lines().map(String::from).collect()
+    }
+
+    /// The ints written that aren't ASCII
+    pub fn values(&self) -> &[Int] {
+        &self.values
+    }
+
+    pub fn take_values(&mut self) -> Vec<Int> {
+        std::mem::take(&mut self.values)
+    }
+}
+
+impl IntWrite for AsciiOutput {
+    fn int_write(&mut self, value: Int) {
+        match u8::try_from(value) {
+            Ok(byte) if byte.is_ascii() => self.text.push(char::from(byte)),
+            _ => self.values.push(value),
+        }
+    }
+}
+
+impl IntWrite for &mut AsciiOutput {
+    fn int_write(&mut self, value: Int) {
+        (**self).int_write(value)
+    }
+}
@@ -0,0 +1,421 @@
+//! Output that draws a picture, and input that reacts to it.
+//!
+//! The screens use interior mutability so a program can write to `&screen` while reading from
+//! `screen.input(..)`, which looks at what's been drawn so far:
+//!
+//! ```text
+//! let screen = Screen::new();
+//! program.run(screen.input(|canvas| Some(joystick(canvas))), &screen)?;
+//! ```
+
+use std::cell::{Cell, Ref, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+
+use super::{IntRead, IntWrite};
+use crate::Int;
+
+/// An (x, y) position, with y increasing downwards
+pub type Point = (Int, Int);
+
+/// A sparse grid of tiles
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Canvas<T = Int> {
+    tiles: HashMap<Point, T>,
+}
+
+impl<T> Default for Canvas<T> {
+    fn default() -> Canvas<T> {
+        Canvas {
+            tiles: HashMap::new(),
+        }
+    }
+}
+
+impl<T> Canvas<T> {
+    pub fn new() -> Canvas<T> {
+        Canvas::default()
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.tiles.get(&point)
+    }
+
+    /// Draw `tile` at `point`, returning what was there before
+    pub fn insert(&mut self, point: Point, tile: T) -> Option<T> {
+        self.tiles.insert(point, tile)
+    }
+
+    /// How many tiles have been drawn
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+    }
+
+    /// Every tile, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.tiles.iter().map(|(&point, tile)| (point, tile))
+    }
+
+    /// Where `tile` is drawn, e.g. the ball in 2019-13. If it's drawn more than once this is any of
+    /// them.
+    pub fn find(&self, tile: &T) -> Option<Point>
+    where
+        T: PartialEq,
+    {
+        self.iter()
+            .find(|(_, other)| *other == tile)
+            .map(|(point, _)| point)
+    }
+
+    pub fn count(&self, tile: &T) -> usize
+    where
+        T: PartialEq,
+    {
+        self.tiles.values().filter(|other| *other == tile).count()
+    }
+
+    /// The top left and bottom right corners (inclusive) of the drawn tiles
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        let mut points = self.tiles.keys();
+        let &first = points.next()?;
+        Some(points.fold(
+            (first, first),
+            |((min_x, min_y), (max_x, max_y)), &(x, y)| {
+                ((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y)))
+            },
+        ))
+    }
+
+    /// One line per row within the bounds, drawing each tile (or `None` for a gap) with `f`
+    pub fn render(&self, f: impl Fn(Option<&T>) -> char) -> String {
+        let ((min_x, min_y), (max_x, max_y)) = match self.bounds() {
+            Some(bounds) => bounds,
+            None => return String::new(),
+        };
+
+        let mut text = String::new();
+        for y in min_y..=max_y {
+            text.extend((min_x..=max_x).map(|x| f(self.get((x, y)))));
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Redraw the whole terminal, e.g. to watch a game being played
+    pub fn draw(&self, out: &mut impl Write, f: impl Fn(Option<&T>) -> char) -> io::Result<()> {
+        // move to the top left and clear the screen
+        write!(out, "\x1b[H\x1b[2J{}", self.render(f))?;
+        out.flush()
+    }
+}
+
+impl fmt::Display for Canvas<char> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(|tile| tile.copied().unwrap_or(' ')))
+    }
+}
+
+/// Draws the (x, y, tile) triples a program writes
+#[derive(Debug, Clone, Default)]
+pub struct Screen {
+    canvas: RefCell<Canvas>,
+    /// The start of a triple that hasn't been completely written yet
+    partial: RefCell<Vec<Int>>,
+}
+
+impl Screen {
+    pub fn new() -> Screen {
+        Screen::default()
+    }
+
+    pub fn canvas(&self) -> Ref<'_, Canvas> {
+        self.canvas.borrow()
+    }
+
+    pub fn into_canvas(self) -> Canvas {
+        self.canvas.into_inner()
+    }
+
+    /// Read whatever `f` returns given what's been drawn so far
+    pub fn input<F: FnMut(&Canvas) -> Option<Int>>(&self, f: F) -> CanvasReader<'_, Int, F> {
+        CanvasReader {
+            canvas: &self.canvas,
+            f,
+        }
+    }
+}
+
+impl IntWrite for &Screen {
+    fn int_write(&mut self, value: Int) {
+        let mut partial = self.partial.borrow_mut();
+        partial.push(value);
+        if let [x, y, tile] = partial[..] {
+            self.canvas.borrow_mut().insert((x, y), tile);
+            partial.clear();
+        }
+    }
+}
+
+impl IntWrite for Screen {
+    fn int_write(&mut self, value: Int) {
+        (&*self).int_write(value)
+    }
+}
+
+impl IntWrite for &mut Screen {
+    fn int_write(&mut self, value: Int) {
+        (&**self).int_write(value)
+    }
+}
+
+/// Draws the text a program writes, a line per row. Ints that aren't ASCII (usually the final
+/// answer) are kept separately, as with `AsciiOutput`.
+#[derive(Debug, Clone, Default)]
+pub struct TextScreen {
+    canvas: RefCell<Canvas<char>>,
+    cursor: Cell<Point>,
+    values: RefCell<Vec<Int>>,
+}
+
+impl TextScreen {
+    pub fn new() -> TextScreen {
+        TextScreen::default()
+    }
+
+    pub fn canvas(&self) -> Ref<'_, Canvas<char>> {
+        self.canvas.borrow()
+    }
+
+    pub fn into_canvas(self) -> Canvas<char> {
+        self.canvas.into_inner()
+    }
+
+    /// The ints written that aren't ASCII
+    pub fn values(&self) -> Ref<'_, [Int]> {
+        Ref::map(self.values.borrow(), Vec::as_slice)
+    }
+
+    /// Start drawing again from the top left, e.g. before the next frame of a video feed
+    pub fn clear(&self) {
+        self.canvas.borrow_mut().clear();
+        self.cursor.set((0, 0));
+    }
+
+    /// Read whatever `f` returns given what's been drawn so far
+    pub fn input<F: FnMut(&Canvas<char>) -> Option<Int>>(&self, f: F) -> CanvasReader<'_, char, F> {
+        CanvasReader {
+            canvas: &self.canvas,
+            f,
+        }
+    }
+}
+
+impl IntWrite for &TextScreen {
+    fn int_write(&mut self, value: Int) {
+        let (x, y) = self.cursor.get();
+        match u8::try_from(value) {
+            Ok(b'\n') => self.cursor.set((0, y + 1)),
+            Ok(byte) if byte.is_ascii() => {
+                self.canvas.borrow_mut().insert((x, y), char::from(byte));
+                self.cursor.set((x + 1, y));
+            }
+            _ => self.values.borrow_mut().push(value),
+        }
+    }
+}
+
+impl IntWrite for TextScreen {
+    fn int_write(&mut self, value: Int) {
+        (&*self).int_write(value)
+    }
+}
+
+impl IntWrite for &mut TextScreen {
+    fn int_write(&mut self, value: Int) {
+        (&**self).int_write(value)
+    }
+}
+
+/// Which way a `Robot` is facing
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    pub fn turn_left(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Right => Direction::Up,
+            Direction::Down => Direction::Right,
+            Direction::Left => Direction::Down,
+        }
+    }
+
+    pub fn turn_right(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    /// The point one step from `point` in this direction
+    pub fn step(self, (x, y): Point) -> Point {
+        match self {
+            Direction::Up => (x, y - 1),
+            Direction::Right => (x + 1, y),
+            Direction::Down => (x, y + 1),
+            Direction::Left => (x - 1, y),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RobotState {
+    canvas: Canvas,
+    position: Point,
+    facing: Direction,
+    paint: Option<Int>,
+}
+
+/// A robot that paints as it goes, like 2019-11's. The program writes pairs of a colour to paint
+/// the robot's tile and a turn (0 for left, 1 for right), after which the robot steps forwards.
+#[derive(Debug, Clone)]
+pub struct Robot {
+    state: RefCell<RobotState>,
+}
+
+impl Default for Robot {
+    fn default() -> Robot {
+        Robot::new()
+    }
+}
+
+impl Robot {
+    /// A robot at (0, 0) facing up
+    pub fn new() -> Robot {
+        Robot::with_canvas(Canvas::new())
+    }
+
+    /// Start on an already painted canvas, e.g. one with a single white tile
+    pub fn with_canvas(canvas: Canvas) -> Robot {
+        Robot {
+            state: RefCell::new(RobotState {
+                canvas,
+                position: (0, 0),
+                facing: Direction::Up,
+                paint: None,
+            }),
+        }
+    }
+
+    pub fn position(&self) -> Point {
+        self.state.borrow().position
+    }
+
+    pub fn facing(&self) -> Direction {
+        self.state.borrow().facing
+    }
+
+    pub fn canvas(&self) -> Ref<'_, Canvas> {
+        Ref::map(self.state.borrow(), |state| &state.canvas)
+    }
+
+    pub fn into_canvas(self) -> Canvas {
+        self.state.into_inner().canvas
+    }
+
+    /// Read whatever `f` returns given the canvas and where the robot is
+    pub fn input<F: FnMut(&Canvas, Point) -> Option<Int>>(&self, f: F) -> RobotReader<'_, F> {
+        RobotReader { robot: self, f }
+    }
+
+    /// Read the colour of the robot's tile, 0 if it hasn't been painted
+    pub fn camera(&self) -> RobotReader<'_, impl FnMut(&Canvas, Point) -> Option<Int>> {
+        self.input(|canvas, position| Some(canvas.get(position).copied().unwrap_or(0)))
+    }
+}
+
+impl IntWrite for &Robot {
+    fn int_write(&mut self, value: Int) {
+        let mut state = self.state.borrow_mut();
+        let colour = match state.paint.take() {
+            Some(colour) => colour,
+            None => {
+                state.paint = Some(value);
+                return;
+            }
+        };
+
+        let position = state.position;
+        state.canvas.insert(position, colour);
+        state.facing = match value {
+            0 => state.facing.turn_left(),
+            _ => state.facing.turn_right(),
+        };
+        state.position = state.facing.step(position);
+    }
+}
+
+impl IntWrite for Robot {
+    fn int_write(&mut self, value: Int) {
+        (&*self).int_write(value)
+    }
+}
+
+impl IntWrite for &mut Robot {
+    fn int_write(&mut self, value: Int) {
+        (&**self).int_write(value)
+    }
+}
+
+/// Reads whatever a closure returns given a screen's canvas, see `Screen::input`
+pub struct CanvasReader<'a, T, F> {
+    canvas: &'a RefCell<Canvas<T>>,
+    f: F,
+}
+
+impl<T, F: FnMut(&Canvas<T>) -> Option<Int>> IntRead for CanvasReader<'_, T, F> {
+    fn int_read(&mut self) -> Option<Int> {
+        (self.f)(&self.canvas.borrow())
+    }
+}
+
+impl<T, F: FnMut(&Canvas<T>) -> Option<Int>> IntRead for &mut CanvasReader<'_, T, F> {
+    fn int_read(&mut self) -> Option<Int> {
+        (**self).int_read()
+    }
+}
+
+/// Reads whatever a closure returns given a robot's canvas and position, see `Robot::input`
+pub struct RobotReader<'a, F> {
+    robot: &'a Robot,
+    f: F,
+}
+
+impl<F: FnMut(&Canvas, Point) -> Option<Int>> IntRead for RobotReader<'_, F> {
+    fn int_read(&mut self) -> Option<Int> {
+        let state = self.robot.state.borrow();
+        (self.f)(&state.canvas, state.position)
+    }
+}
+
+impl<F: FnMut(&Canvas, Point) -> Option<Int>> IntRead for &mut RobotReader<'_, F> {
+    fn int_read(&mut self) -> Option<Int> {
+        (**self).int_read()
+    }
+}
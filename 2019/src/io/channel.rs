@@ -0,0 +1,113 @@
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+
+use super::{IntRead, IntWrite};
+use crate::Int;
+
+/// Reads from a channel, waiting for a value when there isn't one yet.
+/// Once every sender is dropped it reads `None`, so `Program::run` stops with `WaitingForInput`.
+pub struct BlockingReader {
+    receiver: Receiver<Int>,
+}
+
+impl BlockingReader {
+    pub fn new(receiver: Receiver<Int>) -> BlockingReader {
+        BlockingReader { receiver }
+    }
+}
+
+impl IntRead for BlockingReader {
+    fn int_read(&mut self) -> Option<Int> {
+        self.receiver.recv().ok()
+    }
+}
+
+impl IntRead for &mut BlockingReader {
+    fn int_read(&mut self) -> Option<Int> {
+        (**self).int_read()
+    }
+}
+
+/// Reads from a channel without waiting, reading `default` when there's no value yet.
+/// Once every sender is dropped it reads `None`.
+pub struct DefaultReader {
+    receiver: Receiver<Int>,
+    default: Int,
+    /// How many reads in a row returned the default
+    idle_reads: usize,
+}
+
+impl DefaultReader {
+    pub fn new(receiver: Receiver<Int>, default: Int) -> DefaultReader {
+        DefaultReader {
+            receiver,
+            default,
+            idle_reads: 0,
+        }
+    }
+
+    /// How many reads in a row found nothing in the channel, e.g. to tell when a machine is idle
+    pub fn idle_reads(&self) -> usize {
+        self.idle_reads
+    }
+}
+
+impl IntRead for DefaultReader {
+    fn int_read(&mut self) -> Option<Int> {
+        match self.receiver.try_recv() {
+            Ok(value) => {
+                self.idle_reads = 0;
+                Some(value)
+            }
+            Err(TryRecvError::Empty) => {
+                self.idle_reads += 1;
+                Some(self.default)
+            }
+            Err(TryRecvError::Disconnected) => None,
+        }
+    }
+}
+
+impl IntRead for &mut DefaultReader {
+    fn int_read(&mut self) -> Option<Int> {
+        (**self).int_read()
+    }
+}
+
+/// Sends every value written to a channel, values are dropped once the receiver is gone
+#[derive(Clone)]
+pub struct ChannelWriter {
+    sender: Sender<Int>,
+}
+
+impl ChannelWriter {
+    pub fn new(sender: Sender<Int>) -> ChannelWriter {
+        ChannelWriter { sender }
+    }
+}
+
+impl IntWrite for ChannelWriter {
+    fn int_write(&mut self, value: Int) {
+        let _ = self.sender.send(value);
+    }
+}
+
+impl IntWrite for &mut ChannelWriter {
+    fn int_write(&mut self, value: Int) {
+        (**self).int_write(value)
+    }
+}
+
+/// A channel for connecting machines, where the reader waits for values
+pub fn channel() -> (ChannelWriter, BlockingReader) {
+    let (sender, receiver) = mpsc::channel();
+    (ChannelWriter::new(sender), BlockingReader::new(receiver))
+}
+
+/// A channel for connecting machines, where the reader reads `default` when there's no value
+pub fn default_channel(default: Int) -> (ChannelWriter, DefaultReader) {
+    let (sender, receiver) = mpsc::channel();
+    (
+        ChannelWriter::new(sender),
+        DefaultReader::new(receiver, default),
+    )
+}
@@ -1,5 +1,11 @@
+pub mod ascii;
+pub mod canvas;
+pub mod channel;
 pub mod read;
 pub mod write;
 
-pub use read::IntRead;
-pub use write::IntWrite;
+pub use ascii::{AsciiInput, AsciiOutput};
+pub use canvas::{Canvas, Robot, Screen, TextScreen};
+pub use channel::{channel, default_channel, BlockingReader, ChannelWriter, DefaultReader};
+pub use read::{from_fn, from_iter, FnReader, IntRead, IterReader};
+pub use write::{IntWrite, LogWriter};
@@ -1,17 +1,21 @@
-use std::sync::{Arc, Mutex};
+use std::collections::VecDeque;
 
+use crate::word::Word;
 use crate::Int;
 
-pub trait IntRead {
-    fn int_read(&mut self) -> Option<Int>;
+pub trait IntRead<W = Int> {
+    fn int_read(&mut self) -> Option<W>;
 }
 
-impl IntRead for &mut Vec<Int> {
-    fn int_read(&mut self) -> Option<Int> {
-        match self.len() {
-            0 => None,
-            _ => Some(self.remove(0)),
-        }
+impl<W: Word> IntRead<W> for VecDeque<W> {
+    fn int_read(&mut self) -> Option<W> {
+        self.pop_front()
+    }
+}
+
+impl<W: Word> IntRead<W> for &mut VecDeque<W> {
+    fn int_read(&mut self) -> Option<W> {
+        self.pop_front()
     }
 }
 
@@ -21,11 +25,55 @@ impl IntRead for Int {
     }
 }
 
-impl IntRead for Option<Int> {
-    fn int_read(&mut self) -> Option<Int> {
-        match self {
-            Some(int) => int.int_read(),
-            None => None,
-        }
+/// Reads the same value every time, or nothing
+impl<W: Word> IntRead<W> for Option<W> {
+    fn int_read(&mut self) -> Option<W> {
+        self.clone()
+    }
+}
+
+/// Reads the values of an iterator, see `from_iter`
+pub struct IterReader<I> {
+    iter: I,
+}
+
+impl<W: Word, I: Iterator<Item = W>> IntRead<W> for IterReader<I> {
+    fn int_read(&mut self) -> Option<W> {
+        self.iter.next()
+    }
+}
+
+impl<W: Word, I: Iterator<Item = W>> IntRead<W> for &mut IterReader<I> {
+    fn int_read(&mut self) -> Option<W> {
+        self.iter.next()
     }
 }
+
+/// Read the values of an iterator, e.g. `from_iter(phases.iter().copied().chain(once(0)))`
+pub fn from_iter<I: IntoIterator>(iter: I) -> IterReader<I::IntoIter> {
+    IterReader {
+        iter: iter.into_iter(),
+    }
+}
+
+/// Reads whatever a closure returns, see `from_fn`
+pub struct FnReader<F> {
+    f: F,
+}
+
+impl<W: Word, F: FnMut() -> Option<W>> IntRead<W> for FnReader<F> {
+    fn int_read(&mut self) -> Option<W> {
+        (self.f)()
+    }
+}
+
+impl<W: Word, F: FnMut() -> Option<W>> IntRead<W> for &mut FnReader<F> {
+    fn int_read(&mut self) -> Option<W> {
+        (self.f)()
+    }
+}
+
+/// Call `f` for every value the program reads, returning `None` makes it wait for input
+pub fn from_fn<W: Word, F: FnMut() -> Option<W>>(f: F) -> FnReader<F> {
+    FnReader { f }
+}
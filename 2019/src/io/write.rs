@@ -1,11 +1,67 @@
+use std::collections::VecDeque;
+
+use crate::word::Word;
 use crate::Int;
 
-pub trait IntWrite {
-    fn int_write(&mut self, value: Int);
+pub trait IntWrite<W = Int> {
+    fn int_write(&mut self, value: W);
 }
 
-impl IntWrite for &mut Vec<Int> {
-    fn int_write(&mut self, value: Int) {
+impl<W: Word> IntWrite<W> for &mut Vec<W> {
+    fn int_write(&mut self, value: W) {
         self.push(value);
     }
 }
+
+impl<W: Word> IntWrite<W> for &mut VecDeque<W> {
+    fn int_write(&mut self, value: W) {
+        self.push_back(value);
+    }
+}
+
+/// Keeps the last value written
+impl<W: Word> IntWrite<W> for &mut Option<W> {
+    fn int_write(&mut self, value: W) {
+        **self = Some(value);
+    }
+}
+
+/// Writes every value to both writers, e.g. `(&mut output, &mut log)`
+impl<W: Word, A: IntWrite<W>, B: IntWrite<W>> IntWrite<W> for (A, B) {
+    fn int_write(&mut self, value: W) {
+        self.0.int_write(value.clone());
+        self.1.int_write(value);
+    }
+}
+
+/// Prints every value written to stderr before passing it on
+pub struct LogWriter<W> {
+    writer: W,
+    label: String,
+}
+
+impl<W> LogWriter<W> {
+    pub fn new(label: impl Into<String>, writer: W) -> LogWriter<W> {
+        LogWriter {
+            writer,
+            label: label.into(),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<V: Word, W: IntWrite<V>> IntWrite<V> for LogWriter<W> {
+    fn int_write(&mut self, value: V) {
+        eprintln!("{}: {}", self.label, value);
+        self.writer.int_write(value);
+    }
+}
+
+impl<V: Word, W: IntWrite<V>> IntWrite<V> for &mut LogWriter<W> {
+    fn int_write(&mut self, value: V) {
+        (**self).int_write(value)
+    }
+}
@@ -3,12 +3,32 @@
 use anyhow::{anyhow, Result};
 use std::io::{Read, Write};
 
+pub mod adventure;
+pub mod asm;
+pub mod cache;
+pub mod cfg;
+pub mod compile;
+pub mod coverage;
+pub mod debugger;
+pub mod diff;
+pub mod disasm;
+pub mod error;
 pub mod int_code;
 pub mod io;
+pub mod memory;
 pub mod op_code;
 pub mod program;
+pub mod scheduler;
+pub mod snapshot;
+pub mod symbolic;
+pub mod trace;
+pub mod word;
 
+pub use error::IntcodeError;
+pub use memory::Memory;
 pub use program::{Program, StopReason};
+pub use snapshot::Snapshot;
+pub use word::{BigInt, Word};
 
 use int_code::IntCode;
 use io::{IntRead, IntWrite};
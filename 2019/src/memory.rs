@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use super::word::Word;
+use super::Int;
+
+/// How far past the end of the dense region a write may be before it's stored sparsely instead
+const MAX_DENSE_GROWTH: usize = 4096;
+
+/// Anything intcode can be decoded from
+pub trait MemoryRead<W = Int> {
+    /// The value at `address`, or `None` if it's outside of the memory
+    fn int_at(&self, address: usize) -> Option<W>;
+}
+
+impl<W: Word> MemoryRead<W> for [W] {
+    #[inline]
+    fn int_at(&self, address: usize) -> Option<W> {
+        self.get(address).cloned()
+    }
+}
+
+impl<W: Word> MemoryRead<W> for Vec<W> {
+    #[inline]
+    fn int_at(&self, address: usize) -> Option<W> {
+        self.get(address).cloned()
+    }
+}
+
+/// A program's memory, which grows as it's written to.
+/// The program and any addresses near it are kept in a `Vec`, addresses far past the end of it are
+/// stored in a map instead. Addresses that have never been written to read as 0.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Memory<W = Int> {
+    dense: Vec<W>,
+    sparse: HashMap<usize, W>,
+}
+
+impl<W: Word> Memory<W> {
+    pub fn new(ints: Vec<W>) -> Memory<W> {
+        Memory {
+            dense: ints,
+            sparse: HashMap::new(),
+        }
+    }
+
+    /// Rebuild a memory from its dense region and the values stored outside of it. Unlike `set`,
+    /// this never grows the dense region, so the memory is laid out exactly as it was.
+    pub fn from_parts(dense: Vec<W>, sparse: impl IntoIterator<Item = (usize, W)>) -> Memory<W> {
+        let mut memory = Memory::new(dense);
+        for (address, value) in sparse {
+            match memory.dense.get_mut(address) {
+                Some(cell) => *cell = value,
+                None if value.is_zero() => {}
+                None => {
+                    memory.sparse.insert(address, value);
+                }
+            }
+        }
+
+        memory
+    }
+
+    #[inline]
+    pub fn get(&self, address: usize) -> W {
+        match self.dense.get(address) {
+            Some(value) => value.clone(),
+            None => self
+                .sparse
+                .get(&address)
+                .cloned()
+                .unwrap_or_else(|| W::from(0)),
+        }
+    }
+
+    #[inline]
+    pub fn set(&mut self, address: usize, value: W) {
+        if address < self.dense.len() {
+            self.dense[address] = value;
+        } else if address < self.dense.len() + self.dense.len().max(MAX_DENSE_GROWTH) {
+            self.grow(address + 1);
+            self.dense[address] = value;
+        } else if value.is_zero() {
+            self.sparse.remove(&address);
+        } else {
+            self.sparse.insert(address, value);
+        }
+    }
+
+    /// Extend the dense region to at least `len`, moving in any sparse values it now covers
+    fn grow(&mut self, len: usize) {
+        let len = len.max(self.dense.len() * 2);
+        let start = self.dense.len();
+        self.dense.resize(len, W::from(0));
+
+        if !self.sparse.is_empty() {
+            let dense = &mut self.dense;
+            self.sparse.retain(|address, value| {
+                if (start..len).contains(address) {
+                    dense[*address] = value.clone();
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+    }
+
+    /// The dense region of memory (which always contains the program)
+    pub fn dense(&self) -> &[W] {
+        &self.dense
+    }
+
+    /// The addresses (and values) that are stored outside of the dense region
+    pub fn sparse(&self) -> impl Iterator<Item = (usize, W)> + '_ {
+        self.sparse
+            .iter()
+            .map(|(address, value)| (*address, value.clone()))
+    }
+}
+
+impl<W: Word> MemoryRead<W> for Memory<W> {
+    #[inline]
+    fn int_at(&self, address: usize) -> Option<W> {
+        Some(self.get(address))
+    }
+}
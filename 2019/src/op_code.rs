@@ -1,85 +1,225 @@
+use std::fmt;
+
+use super::error::{modes, AddressError, IntcodeError};
 use super::int_code::IntCode;
+use super::memory::MemoryRead;
+use super::word::Word;
 use super::Int;
-use anyhow::{anyhow, Result};
 
-#[derive(Debug)]
-pub enum OpCode {
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OpCode<W = Int> {
     Add {
-        lhs: IntCode,
-        rhs: IntCode,
-        target: IntCode,
+        lhs: IntCode<W>,
+        rhs: IntCode<W>,
+        target: IntCode<W>,
     },
     Mult {
-        lhs: IntCode,
-        rhs: IntCode,
-        target: IntCode,
+        lhs: IntCode<W>,
+        rhs: IntCode<W>,
+        target: IntCode<W>,
     },
     Input {
-        target: IntCode,
+        target: IntCode<W>,
     },
     Output {
-        target: IntCode,
+        target: IntCode<W>,
     },
     JumpIfTrue {
-        test: IntCode,
-        destination: IntCode,
+        test: IntCode<W>,
+        destination: IntCode<W>,
     },
     JumpIfFalse {
-        test: IntCode,
-        destination: IntCode,
+        test: IntCode<W>,
+        destination: IntCode<W>,
     },
     LessThan {
-        lhs: IntCode,
-        rhs: IntCode,
-        target: IntCode,
+        lhs: IntCode<W>,
+        rhs: IntCode<W>,
+        target: IntCode<W>,
     },
     Equals {
-        lhs: IntCode,
-        rhs: IntCode,
-        target: IntCode,
+        lhs: IntCode<W>,
+        rhs: IntCode<W>,
+        target: IntCode<W>,
     },
     AdjustRelativeBase {
-        amount: IntCode,
+        amount: IntCode<W>,
     },
     Halt,
 }
 
-impl OpCode {
+impl<W: Word> OpCode<W> {
     /// How many `Int`s this `OpCode` is built from
+    #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
         match self {
             OpCode::Halt => 1,
             OpCode::Input { .. } | OpCode::Output { .. } | OpCode::AdjustRelativeBase { .. } => 2,
-            OpCode::JumpIfTrue { .. } => 3,
+            OpCode::JumpIfTrue { .. } | OpCode::JumpIfFalse { .. } => 3,
             OpCode::Add { .. }
             | OpCode::Mult { .. }
-            | OpCode::JumpIfFalse { .. }
             | OpCode::LessThan { .. }
             | OpCode::Equals { .. } => 4,
         }
     }
 
+    /// The opcode an instruction is encoded with (without the parameter modes)
+    pub fn code(&self) -> Int {
+        match self {
+            OpCode::Add { .. } => 1,
+            OpCode::Mult { .. } => 2,
+            OpCode::Input { .. } => 3,
+            OpCode::Output { .. } => 4,
+            OpCode::JumpIfTrue { .. } => 5,
+            OpCode::JumpIfFalse { .. } => 6,
+            OpCode::LessThan { .. } => 7,
+            OpCode::Equals { .. } => 8,
+            OpCode::AdjustRelativeBase { .. } => 9,
+            OpCode::Halt => 99,
+        }
+    }
+
+    /// The name of the `OpCode` in disassembly listings
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            OpCode::Add { .. } => "add",
+            OpCode::Mult { .. } => "mult",
+            OpCode::Input { .. } => "in",
+            OpCode::Output { .. } => "out",
+            OpCode::JumpIfTrue { .. } => "jt",
+            OpCode::JumpIfFalse { .. } => "jf",
+            OpCode::LessThan { .. } => "lt",
+            OpCode::Equals { .. } => "eq",
+            OpCode::AdjustRelativeBase { .. } => "arb",
+            OpCode::Halt => "halt",
+        }
+    }
+
+    /// The `OpCode` with the given mnemonic, with `Position(i)` as its `i`th parameter
+    pub fn from_mnemonic(mnemonic: &str) -> Option<OpCode<W>> {
+        let p = IntCode::Position;
+        [
+            OpCode::Add {
+                lhs: p(0),
+                rhs: p(1),
+                target: p(2),
+            },
+            OpCode::Mult {
+                lhs: p(0),
+                rhs: p(1),
+                target: p(2),
+            },
+            OpCode::Input { target: p(0) },
+            OpCode::Output { target: p(0) },
+            OpCode::JumpIfTrue {
+                test: p(0),
+                destination: p(1),
+            },
+            OpCode::JumpIfFalse {
+                test: p(0),
+                destination: p(1),
+            },
+            OpCode::LessThan {
+                lhs: p(0),
+                rhs: p(1),
+                target: p(2),
+            },
+            OpCode::Equals {
+                lhs: p(0),
+                rhs: p(1),
+                target: p(2),
+            },
+            OpCode::AdjustRelativeBase { amount: p(0) },
+            OpCode::Halt,
+        ]
+        .into_iter()
+        .find(|op| op.mnemonic() == mnemonic)
+    }
+
+    /// The parameters of the `OpCode`, in the order they're encoded
+    pub fn parameters(&self) -> Vec<IntCode<W>> {
+        match self.clone() {
+            OpCode::Add { lhs, rhs, target }
+            | OpCode::Mult { lhs, rhs, target }
+            | OpCode::LessThan { lhs, rhs, target }
+            | OpCode::Equals { lhs, rhs, target } => vec![lhs, rhs, target],
+            OpCode::Input { target } | OpCode::Output { target } => vec![target],
+            OpCode::JumpIfTrue { test, destination }
+            | OpCode::JumpIfFalse { test, destination } => {
+                vec![test, destination]
+            }
+            OpCode::AdjustRelativeBase { amount } => vec![amount],
+            OpCode::Halt => vec![],
+        }
+    }
+
+    /// The parameter the `OpCode` writes to, if any
+    pub fn target(&self) -> Option<IntCode<W>> {
+        match self {
+            OpCode::Add { target, .. }
+            | OpCode::Mult { target, .. }
+            | OpCode::LessThan { target, .. }
+            | OpCode::Equals { target, .. }
+            | OpCode::Input { target } => Some(target.clone()),
+            _ => None,
+        }
+    }
+
     /// Read the next `OpCode` start at position `ip`
-    pub fn next(ip: &mut usize, slice: &[Int]) -> Result<OpCode> {
+    pub fn next<M: MemoryRead<W> + ?Sized>(
+        ip: &mut usize,
+        memory: &M,
+    ) -> Result<OpCode<W>, IntcodeError> {
         let start_ip = *ip;
-        let instruction = slice[*ip];
+        let word = memory
+            .int_at(start_ip)
+            .ok_or_else(|| AddressError::MemoryOutOfRange(start_ip).at(start_ip, 0))?;
+        let instruction = word.clamp_to_int();
+        if instruction < 0 || word.to_int().is_none() {
+            return Err(IntcodeError::InvalidOpcode {
+                ip: start_ip,
+                instruction,
+                modes: modes(instruction),
+            });
+        }
+
         *ip += 1;
 
         // get the right int code for the parameter mode
+        let parameter_modes = modes(instruction);
         let mut next_int_code = || {
-            let int_code = slice
-                .get(*ip)
-                .map(|x| {
-                    match aoc_lib::utils::digit_at(instruction as usize, (*ip - start_ip) + 1) {
-                        1 => IntCode::Immediate(*x),
-                        2 => IntCode::Relative(*x),
-                        _ => IntCode::Position(*x as usize),
+            let parameter = *ip - start_ip - 1;
+            let x = memory
+                .int_at(*ip)
+                .ok_or_else(|| AddressError::MemoryOutOfRange(*ip).at(start_ip, instruction))?;
+
+            let int_code =
+                match parameter_modes[parameter] {
+                    0 if x.is_negative() => {
+                        return Err(AddressError::NegativeAddress(x.clamp_to_int())
+                            .at(start_ip, instruction));
                     }
-                })
-                .ok_or_else(|| anyhow!("No value at position: {}", ip));
+                    0 => match x.to_int() {
+                        Some(x) => IntCode::Position(x as usize),
+                        None => {
+                            return Err(AddressError::MemoryOutOfRange(usize::MAX)
+                                .at(start_ip, instruction))
+                        }
+                    },
+                    1 => IntCode::Immediate(x),
+                    2 => IntCode::Relative(x),
+                    _ => {
+                        return Err(IntcodeError::InvalidMode {
+                            ip: start_ip,
+                            instruction,
+                            modes: modes(instruction),
+                            parameter,
+                        })
+                    }
+                };
 
             *ip += 1;
-            int_code
+            Ok(int_code)
         };
 
         Ok(match instruction % 100 {
@@ -121,10 +261,24 @@ impl OpCode {
                 amount: next_int_code()?,
             },
             99 => OpCode::Halt,
-            _ => unreachable!(
-                "Received unrecognised instruction: {} at index: {}",
-                instruction, *ip
-            ),
+            _ => {
+                return Err(IntcodeError::InvalidOpcode {
+                    ip: start_ip,
+                    instruction,
+                    modes: modes(instruction),
+                })
+            }
         })
     }
 }
+
+impl<W: Word> fmt::Display for OpCode<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.mnemonic())?;
+        for (i, parameter) in self.parameters().iter().enumerate() {
+            write!(f, "{}{}", if i == 0 { " " } else { ", " }, parameter)?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,100 +1,451 @@
+use super::cache::DecodeCache;
+use super::coverage::Coverage;
+use super::error::{modes, AddressError, IntcodeError};
+use super::int_code::IntCode;
 use super::io::{IntRead, IntWrite};
+use super::memory::Memory;
 use super::op_code::OpCode;
+use super::snapshot::Snapshot;
+use super::trace::{Step, Tracer};
+use super::word::Word;
 use super::Int;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum StopReason {
+pub enum StopReason<W = Int> {
     Halt,
+    /// The program needs more input, running it again retries the read
     WaitingForInput,
+    /// The program wrote a value (only from `run_until_output`)
+    Output(W),
+    /// The program ran as many instructions as it was allowed to (only from `run_with_budget`)
+    BudgetExhausted,
 }
 
+/// An intcode machine computing with `W`s, see `word` for the options
 #[derive(Clone)]
-pub struct Program {
+pub struct Program<W = Int> {
     /// Instruction Pointer
     ip: usize,
     /// Relative Base (see aoc 2019-9)
     rb: usize,
-    /// Program's memory (the raw int code)
-    memory: Vec<Int>,
+    /// Program's memory (the raw int code), grows as the program writes past its end
+    memory: Memory<W>,
+    /// Instructions that have already been decoded, `None` when every instruction is decoded as it
+    /// runs
+    cache: Option<DecodeCache<W>>,
+    /// Whether arithmetic that overflows is an error, rather than wrapping around
+    checked: bool,
+    /// What's been executed and written, `None` unless it's enabled with `with_coverage`
+    coverage: Option<Coverage<W>>,
 }
 
-impl Program {
-    pub fn new(mut ints: Vec<Int>) -> Program {
-        // reserve more space in the program's memory
-        ints.resize(ints.len() * 2, 0);
-
+impl<W: Word> Program<W> {
+    /// A program computing with another word type than `Int`, e.g. `Program::<i128>::with_words`
+    pub fn with_words(words: Vec<W>) -> Program<W> {
         Program {
             ip: 0,
             rb: 0,
-            memory: ints,
+            memory: Memory::new(words),
+            cache: Some(DecodeCache::default()),
+            checked: false,
+            coverage: None,
         }
     }
 
-    pub fn get_memory(&self) -> Vec<Int> {
-        self.memory.clone()
+    /// Whether to keep decoded instructions around (the default), mostly to compare their speed
+    pub fn with_decode_cache(mut self, enabled: bool) -> Program<W> {
+        self.cache = enabled.then(DecodeCache::default);
+        self
     }
 
-    pub fn run_no_io(&mut self) -> StopReason {
-        self.run(None, &mut (vec![] as Vec<Int>))
+    /// Fail with `IntcodeError::Overflow` when `Add`, `Mult` or the relative base overflow, instead
+    /// of wrapping around (the default)
+    pub fn with_checked_arithmetic(mut self, checked: bool) -> Program<W> {
+        self.checked = checked;
+        self
     }
 
-    pub fn run<R, W>(&mut self, mut input: R, mut output: W) -> StopReason
-    where
-        R: IntRead,
-        W: IntWrite,
-    {
-        while let Ok(op_code) = OpCode::next(&mut self.ip, &self.memory) {
-            match op_code {
-                OpCode::Add { lhs, rhs, target } => {
-                    self.memory[target.as_address(self.rb).unwrap()] =
-                        lhs.as_int(&self.memory, self.rb) + rhs.as_int(&self.memory, self.rb);
-                }
-                OpCode::Mult { lhs, rhs, target } => {
-                    self.memory[target.as_address(self.rb).unwrap()] =
-                        lhs.as_int(&self.memory, self.rb) * rhs.as_int(&self.memory, self.rb);
-                }
-                OpCode::Input { target } => match input.int_read() {
-                    Some(x) => self.memory[target.as_address(self.rb).unwrap()] = x,
-                    None => {
-                        // rewind the instruction pointer to the Input instruction
-                        self.ip -= op_code.len();
-                        return StopReason::WaitingForInput;
-                    }
-                },
-                OpCode::Output { target } => output.int_write(target.as_int(&self.memory, self.rb)),
-                OpCode::JumpIfTrue { test, destination } => {
-                    if test.as_int(&self.memory, self.rb) != 0 {
-                        self.ip = destination.as_int(&self.memory, self.rb) as usize;
-                    }
+    /// Track which addresses are executed and log every write, see `Coverage`
+    pub fn with_coverage(mut self, enabled: bool) -> Program<W> {
+        self.coverage = enabled.then(Coverage::new);
+        self
+    }
+
+    /// What's been executed and written so far, if it's being tracked
+    pub fn coverage(&self) -> Option<&Coverage<W>> {
+        self.coverage.as_ref()
+    }
+
+    /// Take what's been tracked so far and start tracking again from scratch, e.g. between runs
+    pub fn take_coverage(&mut self) -> Option<Coverage<W>> {
+        self.coverage.as_mut().map(std::mem::take)
+    }
+
+    /// Instruction Pointer
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+
+    /// Relative Base
+    pub fn rb(&self) -> usize {
+        self.rb
+    }
+
+    pub fn memory(&self) -> &Memory<W> {
+        &self.memory
+    }
+
+    /// The dense region of the program's memory, see `Memory`
+    pub fn get_memory(&self) -> Vec<W> {
+        self.memory.dense().to_vec()
+    }
+
+    pub fn run_no_io(&mut self) -> Result<StopReason<W>, IntcodeError> {
+        self.run(None, &mut Vec::new())
+    }
+
+    fn read(&self, param: &IntCode<W>) -> Result<W, AddressError> {
+        param.as_int(&self.memory, self.rb)
+    }
+
+    fn write(&mut self, param: &IntCode<W>, value: W) -> Result<(), AddressError> {
+        let address = param.as_address(self.rb)?;
+        if let Some(coverage) = &mut self.coverage {
+            coverage.write(address, value.clone());
+        }
+        self.memory.set(address, value);
+        if let Some(cache) = &mut self.cache {
+            cache.invalidate(address);
+        }
+        Ok(())
+    }
+
+    fn jump(&mut self, destination: &IntCode<W>) -> Result<(), AddressError> {
+        let destination = self.read(destination)?;
+        match destination.to_int() {
+            _ if destination.is_negative() => {
+                Err(AddressError::NegativeAddress(destination.clamp_to_int()))
+            }
+            Some(ip) => {
+                self.ip = ip as usize;
+                Ok(())
+            }
+            None => Err(AddressError::MemoryOutOfRange(usize::MAX)),
+        }
+    }
+
+    /// Reports an error in the instruction at `ip`, which is only read once something fails to keep
+    /// it out of every step
+    fn fail(&self, ip: usize) -> impl Fn(AddressError) -> IntcodeError + '_ {
+        move |e| e.at(ip, self.memory.get(ip).clamp_to_int())
+    }
+
+    fn overflow(&self, ip: usize) -> IntcodeError {
+        let instruction = self.memory.get(ip).clamp_to_int();
+        IntcodeError::Overflow {
+            ip,
+            instruction,
+            modes: modes(instruction),
+        }
+    }
+
+    /// Decode the instruction at the ip and move the ip past it
+    #[inline]
+    fn decode(&mut self) -> Result<OpCode<W>, IntcodeError> {
+        let ip = self.ip;
+        match &mut self.cache {
+            Some(cache) => match cache.get(ip) {
+                Some(op_code) => {
+                    self.ip += op_code.len();
+                    Ok(op_code)
                 }
-                OpCode::JumpIfFalse { test, destination } => {
-                    if test.as_int(&self.memory, self.rb) == 0 {
-                        self.ip = destination.as_int(&self.memory, self.rb) as usize;
+                None => {
+                    let op_code = OpCode::next(&mut self.ip, &self.memory)?;
+                    // the cache is dense too, so instructions stored sparsely aren't kept
+                    if ip < self.memory.dense().len() {
+                        cache.insert(ip, op_code.clone());
                     }
+                    Ok(op_code)
                 }
-                OpCode::LessThan { lhs, rhs, target } => {
-                    self.memory[target.as_address(self.rb).unwrap()] =
-                        if lhs.as_int(&self.memory, self.rb) < rhs.as_int(&self.memory, self.rb) {
-                            1
-                        } else {
-                            0
-                        }
+            },
+            None => OpCode::next(&mut self.ip, &self.memory),
+        }
+    }
+
+    pub fn run<R, O>(&mut self, input: R, output: O) -> Result<StopReason<W>, IntcodeError>
+    where
+        R: IntRead<W>,
+        O: IntWrite<W>,
+    {
+        self.run_traced(input, output, &mut ())
+    }
+
+    /// Run until the program writes a value, which is returned as `StopReason::Output`
+    pub fn run_until_output<R: IntRead<W>>(
+        &mut self,
+        mut input: R,
+    ) -> Result<StopReason<W>, IntcodeError> {
+        let mut output = None;
+        loop {
+            if let Some(reason) = self.step(&mut input, &mut &mut output)? {
+                return Ok(reason);
+            }
+            if let Some(value) = output {
+                return Ok(StopReason::Output(value));
+            }
+        }
+    }
+
+    /// Like `run`, but stops with `StopReason::BudgetExhausted` after `budget` instructions
+    pub fn run_with_budget<R, O>(
+        &mut self,
+        mut input: R,
+        mut output: O,
+        budget: u64,
+    ) -> Result<StopReason<W>, IntcodeError>
+    where
+        R: IntRead<W>,
+        O: IntWrite<W>,
+    {
+        for _ in 0..budget {
+            if let Some(reason) = self.step(&mut input, &mut output)? {
+                return Ok(reason);
+            }
+        }
+
+        Ok(StopReason::BudgetExhausted)
+    }
+
+    /// Like `run`, but reports every instruction it executes to `tracer`
+    pub fn run_traced<R, O, T>(
+        &mut self,
+        mut input: R,
+        mut output: O,
+        tracer: &mut T,
+    ) -> Result<StopReason<W>, IntcodeError>
+    where
+        R: IntRead<W>,
+        O: IntWrite<W>,
+        T: Tracer<W>,
+    {
+        loop {
+            if let Some(reason) = self.step_traced(&mut input, &mut output, tracer)? {
+                return Ok(reason);
+            }
+        }
+    }
+
+    /// Execute a single instruction, returns why the program stopped if it did (which is only ever
+    /// `Halt` or `WaitingForInput`)
+    pub fn step<R, O>(
+        &mut self,
+        input: &mut R,
+        output: &mut O,
+    ) -> Result<Option<StopReason<W>>, IntcodeError>
+    where
+        R: IntRead<W>,
+        O: IntWrite<W>,
+    {
+        self.step_traced(input, output, &mut ())
+    }
+
+    /// What a traced step looks like before it's executed
+    fn begin_step(&self, ip: usize, op_code: OpCode<W>) -> Step<W> {
+        // the parameter that's written to is always the last one
+        let target = op_code.target();
+        let parameters = op_code.parameters();
+        let reads = parameters.len() - target.as_ref().map_or(0, |_| 1);
+        let mut operands = [None, None, None];
+        for (operand, parameter) in operands.iter_mut().zip(&parameters[..reads]) {
+            *operand = self.read(parameter).ok();
+        }
+
+        Step {
+            ip,
+            op_code,
+            operands,
+            write: target
+                .and_then(|target| target.as_address(self.rb).ok())
+                .map(|address| (address, W::from(0))),
+            input: None,
+            output: None,
+        }
+    }
+
+    /// Fill in what a traced step did once it's executed
+    fn end_step(&self, mut step: Step<W>) -> Step<W> {
+        if let Some((address, value)) = &mut step.write {
+            *value = self.memory.get(*address);
+        }
+        match step.op_code {
+            OpCode::Input { .. } => step.input = step.write.as_ref().map(|(_, v)| v.clone()),
+            OpCode::Output { .. } => step.output = step.operands[0].clone(),
+            _ => {}
+        }
+
+        step
+    }
+
+    #[inline]
+    pub fn step_traced<R, O, T>(
+        &mut self,
+        input: &mut R,
+        output: &mut O,
+        tracer: &mut T,
+    ) -> Result<Option<StopReason<W>>, IntcodeError>
+    where
+        R: IntRead<W>,
+        O: IntWrite<W>,
+        T: Tracer<W>,
+    {
+        let start_ip = self.ip;
+        let op_code = self.decode()?;
+        if let Some(coverage) = &mut self.coverage {
+            coverage.execute(start_ip);
+        }
+        let step = match T::ENABLED {
+            true => Some(self.begin_step(start_ip, op_code.clone())),
+            false => None,
+        };
+        let result = self.execute(start_ip, &op_code, input, output);
+        match step {
+            // a read that has to wait isn't a step, but one that fails is still traced
+            Some(_) if result == Ok(Some(StopReason::WaitingForInput)) => {}
+            Some(step) => tracer.trace(&self.end_step(step)),
+            None => {}
+        }
+
+        result
+    }
+
+    /// Execute an already decoded instruction
+    #[inline]
+    fn execute<R, O>(
+        &mut self,
+        start_ip: usize,
+        op_code: &OpCode<W>,
+        input: &mut R,
+        output: &mut O,
+    ) -> Result<Option<StopReason<W>>, IntcodeError>
+    where
+        R: IntRead<W>,
+        O: IntWrite<W>,
+    {
+        let mut reason = None;
+
+        match op_code {
+            OpCode::Add { lhs, rhs, target } => {
+                let (lhs, rhs) = (
+                    self.read(lhs).map_err(self.fail(start_ip))?,
+                    self.read(rhs).map_err(self.fail(start_ip))?,
+                );
+                let value = match self.checked {
+                    true => lhs
+                        .checked_add(&rhs)
+                        .ok_or_else(|| self.overflow(start_ip))?,
+                    false => lhs.wrapping_add(&rhs),
+                };
+                self.write(target, value).map_err(self.fail(start_ip))?;
+            }
+            OpCode::Mult { lhs, rhs, target } => {
+                let (lhs, rhs) = (
+                    self.read(lhs).map_err(self.fail(start_ip))?,
+                    self.read(rhs).map_err(self.fail(start_ip))?,
+                );
+                let value = match self.checked {
+                    true => lhs
+                        .checked_mul(&rhs)
+                        .ok_or_else(|| self.overflow(start_ip))?,
+                    false => lhs.wrapping_mul(&rhs),
+                };
+                self.write(target, value).map_err(self.fail(start_ip))?;
+            }
+            OpCode::Input { target } => match input.int_read() {
+                Some(x) => self.write(target, x).map_err(self.fail(start_ip))?,
+                None => {
+                    // rewind the instruction pointer to the Input instruction
+                    self.ip = start_ip;
+                    return Ok(Some(StopReason::WaitingForInput));
                 }
-                OpCode::Equals { lhs, rhs, target } => {
-                    self.memory[target.as_address(self.rb).unwrap()] =
-                        if lhs.as_int(&self.memory, self.rb) == rhs.as_int(&self.memory, self.rb) {
-                            1
-                        } else {
-                            0
-                        }
+            },
+            OpCode::Output { target } => {
+                output.int_write(self.read(target).map_err(self.fail(start_ip))?)
+            }
+            OpCode::JumpIfTrue { test, destination } => {
+                if !self.read(test).map_err(self.fail(start_ip))?.is_zero() {
+                    self.jump(destination).map_err(self.fail(start_ip))?;
                 }
-                OpCode::AdjustRelativeBase { amount } => {
-                    self.rb = (self.rb as Int + amount.as_int(&self.memory, self.rb)) as usize;
+            }
+            OpCode::JumpIfFalse { test, destination } => {
+                if self.read(test).map_err(self.fail(start_ip))?.is_zero() {
+                    self.jump(destination).map_err(self.fail(start_ip))?;
                 }
-                OpCode::Halt => return StopReason::Halt,
             }
+            OpCode::LessThan { lhs, rhs, target } => {
+                let less = self.read(lhs).map_err(self.fail(start_ip))?
+                    < self.read(rhs).map_err(self.fail(start_ip))?;
+                self.write(target, W::from(less as Int))
+                    .map_err(self.fail(start_ip))?;
+            }
+            OpCode::Equals { lhs, rhs, target } => {
+                let equal = self.read(lhs).map_err(self.fail(start_ip))?
+                    == self.read(rhs).map_err(self.fail(start_ip))?;
+                self.write(target, W::from(equal as Int))
+                    .map_err(self.fail(start_ip))?;
+            }
+            OpCode::AdjustRelativeBase { amount } => {
+                let amount = self.read(amount).map_err(self.fail(start_ip))?;
+                self.rb = match amount
+                    .to_int()
+                    .and_then(|a| (self.rb as Int).checked_add(a))
+                {
+                    Some(rb) => rb as usize,
+                    None if self.checked => return Err(self.overflow(start_ip)),
+                    None => (self.rb as Int).wrapping_add(amount.clamp_to_int()) as usize,
+                };
+            }
+            OpCode::Halt => reason = Some(StopReason::Halt),
         }
 
-        unreachable!("Unexpected end of program!")
+        Ok(reason)
+    }
+}
+
+/// Snapshots only hold `Int`s
+impl Program {
+    pub fn new(ints: Vec<Int>) -> Program {
+        Program::with_words(ints)
+    }
+
+    /// Resume a program from a snapshot (its pending I/O is left in the snapshot)
+    pub fn from_snapshot(snapshot: &Snapshot) -> Program {
+        Program {
+            ip: snapshot.ip,
+            rb: snapshot.rb,
+            memory: snapshot.memory.clone(),
+            cache: Some(DecodeCache::default()),
+            checked: false,
+            coverage: None,
+        }
+    }
+
+    /// The state of the machine, without any pending I/O (see `Snapshot::with_io`)
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            ip: self.ip,
+            rb: self.rb,
+            memory: self.memory.clone(),
+            input: vec![],
+            output: vec![],
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.ip = snapshot.ip;
+        self.rb = snapshot.rb;
+        self.memory = snapshot.memory.clone();
+        if let Some(cache) = &mut self.cache {
+            cache.clear();
+        }
     }
 }
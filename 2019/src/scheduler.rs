@@ -0,0 +1,301 @@
+//! Runs a network of machines on a single thread, taking turns in a fixed order so every run is
+//! deterministic. Where each output goes is decided by a `Topology`.
+
+use std::collections::VecDeque;
+
+use super::error::IntcodeError;
+use super::io::IntRead;
+use super::program::{Program, StopReason};
+use super::Int;
+
+/// Where a machine's output is delivered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Route {
+    /// To the input of the machine at this index
+    Machine(usize, Vec<Int>),
+    /// Out of the network, see `Scheduler::take_output`
+    Outside(Vec<Int>),
+}
+
+/// Decides where the outputs of machines go
+pub trait Topology {
+    /// Route the `value` written by machine `from` (out of `machines`), or `None` to hold on to it
+    /// (e.g. until a whole packet has been written)
+    fn route(&mut self, from: usize, value: Int, machines: usize) -> Option<Route>;
+}
+
+/// Each machine's output is the next one's input, the last machine's output leaves the network
+pub struct Pipeline;
+
+impl Topology for Pipeline {
+    fn route(&mut self, from: usize, value: Int, machines: usize) -> Option<Route> {
+        match from + 1 {
+            next if next < machines => Some(Route::Machine(next, vec![value])),
+            _ => Some(Route::Outside(vec![value])),
+        }
+    }
+}
+
+/// Each machine's output is the next one's input, the last machine's output goes to the first
+pub struct Ring;
+
+impl Topology for Ring {
+    fn route(&mut self, from: usize, value: Int, machines: usize) -> Option<Route> {
+        Some(Route::Machine((from + 1) % machines, vec![value]))
+    }
+}
+
+/// Machines write packets of `packet_len` ints, starting with the address of the machine it's for.
+/// The rest of the packet is delivered to that machine, packets for any other address leave the
+/// network whole.
+pub struct Network {
+    packet_len: usize,
+    partial: Vec<Vec<Int>>,
+}
+
+impl Network {
+    pub fn new(packet_len: usize) -> Network {
+        Network {
+            packet_len,
+            partial: vec![],
+        }
+    }
+}
+
+impl Topology for Network {
+    fn route(&mut self, from: usize, value: Int, machines: usize) -> Option<Route> {
+        if self.partial.len() < machines {
+            self.partial.resize(machines, vec![]);
+        }
+
+        let packet = &mut self.partial[from];
+        packet.push(value);
+        if packet.len() < self.packet_len {
+            return None;
+        }
+
+        let packet = std::mem::take(packet);
+        match usize::try_from(packet[0]) {
+            Ok(address) if address < machines => {
+                Some(Route::Machine(address, packet[1..].to_vec()))
+            }
+            _ => Some(Route::Outside(packet)),
+        }
+    }
+}
+
+/// Why the scheduler stopped
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Stop {
+    /// Every machine halted
+    Halted,
+    /// No machine can make progress without more input
+    Idle,
+    /// The predicate passed to `run_until` returned true
+    Predicate,
+}
+
+struct Machine {
+    program: Program,
+    input: VecDeque<Int>,
+    halted: bool,
+    /// Whether it stopped to wait for input on its last turn
+    waiting: bool,
+    /// How many times in a row it's read the default input
+    idle_reads: usize,
+    last_output: Option<Int>,
+}
+
+impl Machine {
+    fn is_idle(&self, default_input: Option<Int>) -> bool {
+        match default_input {
+            _ if self.halted => true,
+            // polling machines are idle once they've found nothing to read twice in a row
+            Some(_) => self.input.is_empty() && self.idle_reads >= 2,
+            None => self.waiting && self.input.is_empty(),
+        }
+    }
+}
+
+/// A machine's input during its turn
+struct TurnInput<'a> {
+    queue: &'a mut VecDeque<Int>,
+    idle_reads: &'a mut usize,
+    default: Option<Int>,
+    /// Whether it's read the default input this turn
+    starved: bool,
+}
+
+impl IntRead for &mut TurnInput<'_> {
+    fn int_read(&mut self) -> Option<Int> {
+        if let Some(value) = self.queue.pop_front() {
+            *self.idle_reads = 0;
+            return Some(value);
+        }
+
+        // a polling machine's turn ends the second time it finds nothing to read
+        match self.default {
+            Some(default) if !self.starved => {
+                self.starved = true;
+                *self.idle_reads += 1;
+                Some(default)
+            }
+            _ => None,
+        }
+    }
+}
+
+pub struct Scheduler<T: Topology> {
+    machines: Vec<Machine>,
+    topology: T,
+    /// What machines read when there's no input for them, `None` means they wait for input
+    default_input: Option<Int>,
+    /// How many instructions a machine may run in a single turn
+    budget: Option<u64>,
+    /// Everything that left the network, along with the machine it came from
+    output: Vec<(usize, Vec<Int>)>,
+}
+
+impl<T: Topology> Scheduler<T> {
+    pub fn new(programs: Vec<Program>, topology: T) -> Scheduler<T> {
+        Scheduler {
+            machines: programs
+                .into_iter()
+                .map(|program| Machine {
+                    program,
+                    input: VecDeque::new(),
+                    halted: false,
+                    waiting: false,
+                    idle_reads: 0,
+                    last_output: None,
+                })
+                .collect(),
+            topology,
+            default_input: None,
+            budget: None,
+            output: vec![],
+        }
+    }
+
+    /// Let machines read `default` instead of waiting when there's no input for them
+    pub fn with_default_input(mut self, default: Int) -> Scheduler<T> {
+        self.default_input = Some(default);
+        self
+    }
+
+    /// End a machine's turn after `budget` instructions, so a busy machine can't hold up the others
+    pub fn with_budget(mut self, budget: u64) -> Scheduler<T> {
+        self.budget = Some(budget);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.machines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.machines.is_empty()
+    }
+
+    pub fn program(&self, machine: usize) -> &Program {
+        &self.machines[machine].program
+    }
+
+    /// Queue input for a machine
+    pub fn send(&mut self, machine: usize, values: impl IntoIterator<Item = Int>) {
+        self.machines[machine].input.extend(values);
+    }
+
+    /// The last value a machine wrote, wherever it was routed
+    pub fn last_output(&self, machine: usize) -> Option<Int> {
+        self.machines[machine].last_output
+    }
+
+    /// Everything that's left the network so far
+    pub fn output(&self) -> &[(usize, Vec<Int>)] {
+        &self.output
+    }
+
+    pub fn take_output(&mut self) -> Vec<(usize, Vec<Int>)> {
+        std::mem::take(&mut self.output)
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.machines.iter().all(|m| m.halted)
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.machines.iter().all(|m| m.is_idle(self.default_input))
+    }
+
+    /// Run a machine until it halts, runs out of input or uses up its budget, then route its output
+    fn turn(&mut self, index: usize) -> Result<(), IntcodeError> {
+        let machine = &mut self.machines[index];
+        if machine.halted || (machine.waiting && machine.input.is_empty()) {
+            return Ok(());
+        }
+
+        let mut output = vec![];
+        let mut input = TurnInput {
+            queue: &mut machine.input,
+            idle_reads: &mut machine.idle_reads,
+            default: self.default_input,
+            starved: false,
+        };
+        let reason = match self.budget {
+            Some(budget) => machine
+                .program
+                .run_with_budget(&mut input, &mut output, budget)?,
+            None => machine.program.run(&mut input, &mut output)?,
+        };
+        machine.halted = reason == StopReason::Halt;
+        machine.waiting = reason == StopReason::WaitingForInput && self.default_input.is_none();
+        machine.last_output = output.last().copied().or(machine.last_output);
+
+        let machines = self.machines.len();
+        for value in output {
+            match self.topology.route(index, value, machines) {
+                Some(Route::Machine(to, values)) => self.machines[to].input.extend(values),
+                Some(Route::Outside(values)) => self.output.push((index, values)),
+                None => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Give every machine one turn, in order
+    pub fn run_round(&mut self) -> Result<(), IntcodeError> {
+        for index in 0..self.machines.len() {
+            self.turn(index)?;
+        }
+
+        Ok(())
+    }
+
+    /// Run until every machine halts or the network goes idle
+    pub fn run(&mut self) -> Result<Stop, IntcodeError> {
+        self.run_until(|_| false)
+    }
+
+    /// Run until every machine halts, the network goes idle, or `predicate` returns true (it's
+    /// checked after every round)
+    pub fn run_until(
+        &mut self,
+        mut predicate: impl FnMut(&Scheduler<T>) -> bool,
+    ) -> Result<Stop, IntcodeError> {
+        loop {
+            self.run_round()?;
+
+            if predicate(self) {
+                return Ok(Stop::Predicate);
+            }
+            if self.is_halted() {
+                return Ok(Stop::Halted);
+            }
+            if self.is_idle() {
+                return Ok(Stop::Idle);
+            }
+        }
+    }
+}
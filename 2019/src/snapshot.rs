@@ -0,0 +1,97 @@
+//! The full state of an intcode machine, see `Program::snapshot` and `Program::restore`.
+//!
+//! Snapshots are (de)serialized with serde, and saved to files as JSON:
+//!
+//! ```text
+//! {"ip":4,"rb":0,"memory":[1,0,0,3,99],"sparse":[[10000,7]],"input":[1,2],"output":[]}
+//! ```
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use super::memory::Memory;
+use super::Int;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "Fields", into = "Fields")]
+pub struct Snapshot {
+    pub ip: usize,
+    pub rb: usize,
+    pub memory: Memory,
+    /// Input that was queued but not read by the program yet
+    pub input: Vec<Int>,
+    /// Output that was written by the program but not handled yet
+    pub output: Vec<Int>,
+}
+
+/// How a snapshot is (de)serialized, with the memory split into its dense and sparse parts
+#[derive(Serialize, Deserialize)]
+struct Fields {
+    ip: usize,
+    rb: usize,
+    memory: Vec<Int>,
+    #[serde(default)]
+    sparse: Vec<(usize, Int)>,
+    #[serde(default)]
+    input: Vec<Int>,
+    #[serde(default)]
+    output: Vec<Int>,
+}
+
+impl From<Fields> for Snapshot {
+    fn from(fields: Fields) -> Snapshot {
+        Snapshot {
+            ip: fields.ip,
+            rb: fields.rb,
+            memory: Memory::from_parts(fields.memory, fields.sparse),
+            input: fields.input,
+            output: fields.output,
+        }
+    }
+}
+
+impl From<Snapshot> for Fields {
+    fn from(snapshot: Snapshot) -> Fields {
+        Fields {
+            ip: snapshot.ip,
+            rb: snapshot.rb,
+            sparse: snapshot.sparse(),
+            memory: snapshot.memory.dense().to_vec(),
+            input: snapshot.input,
+            output: snapshot.output,
+        }
+    }
+}
+
+impl Snapshot {
+    /// Include pending I/O, which lives outside of the `Program`
+    pub fn with_io(
+        mut self,
+        input: impl IntoIterator<Item = Int>,
+        output: impl IntoIterator<Item = Int>,
+    ) -> Snapshot {
+        self.input = input.into_iter().collect();
+        self.output = output.into_iter().collect();
+        self
+    }
+
+    fn sparse(&self) -> Vec<(usize, Int)> {
+        let mut sparse = self.memory.sparse().collect::<Vec<_>>();
+        sparse.sort_unstable();
+        sparse
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        Ok(fs::write(path, serde_json::to_string(self)?)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Snapshot> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path)
+            .map_err(|e| anyhow!("failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&json).map_err(|e| anyhow!("{}: {}", path.display(), e))
+    }
+}
@@ -0,0 +1,606 @@
+//! Runs a program with some of its memory cells or inputs left as symbols, building an expression
+//! for everything computed from them. Searches like 2019-2's noun and verb can then be solved
+//! algebraically when the result is linear, see `solve_memory`, and `search` tries every
+//! combination on every core when it isn't.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::ops::{Add, Mul, RangeInclusive};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::thread;
+
+use super::error::{AddressError, IntcodeError};
+use super::int_code::IntCode;
+use super::memory::MemoryRead;
+use super::op_code::OpCode;
+use super::program::{Program, StopReason};
+use super::Int;
+
+/// How many instructions `solve_memory` runs the program for symbolically
+const BUDGET: u64 = 10_000_000;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Symbol {
+    /// The initial value of a memory cell
+    Memory(usize),
+    /// The nth value read as input (starting at 0)
+    Input(usize),
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Symbol::Memory(address) => write!(f, "[{}]", address),
+            Symbol::Input(n) => write!(f, "in{}", n),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Expr {
+    Const(Int),
+    Symbol(Symbol),
+    Add(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    /// 1 if the left side is less than the right, otherwise 0
+    LessThan(Box<Expr>, Box<Expr>),
+    /// 1 if both sides are equal, otherwise 0
+    Equals(Box<Expr>, Box<Expr>),
+    /// A value read from an address that depends on a symbol
+    Unknown,
+}
+
+impl Expr {
+    pub fn as_const(&self) -> Option<Int> {
+        match self {
+            Expr::Const(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn less_than(lhs: Expr, rhs: Expr) -> Expr {
+        match (lhs, rhs) {
+            (Expr::Const(lhs), Expr::Const(rhs)) => Expr::Const((lhs < rhs) as Int),
+            (lhs, rhs) => Expr::LessThan(Box::new(lhs), Box::new(rhs)),
+        }
+    }
+
+    pub fn equals(lhs: Expr, rhs: Expr) -> Expr {
+        match (lhs, rhs) {
+            (Expr::Const(lhs), Expr::Const(rhs)) => Expr::Const((lhs == rhs) as Int),
+            (lhs, rhs) if lhs == rhs && lhs != Expr::Unknown => Expr::Const(1),
+            (lhs, rhs) => Expr::Equals(Box::new(lhs), Box::new(rhs)),
+        }
+    }
+
+    /// The expression as a constant plus a multiple of each symbol, if it is one
+    pub fn linear(&self) -> Option<Linear> {
+        match self {
+            Expr::Const(value) => Some(Linear {
+                constant: *value,
+                coefficients: BTreeMap::new(),
+            }),
+            Expr::Symbol(symbol) => Some(Linear {
+                constant: 0,
+                coefficients: [(*symbol, 1)].into_iter().collect(),
+            }),
+            Expr::Add(lhs, rhs) => lhs.linear()?.add(&rhs.linear()?),
+            Expr::Mul(lhs, rhs) => {
+                let (lhs, rhs) = (lhs.linear()?, rhs.linear()?);
+                match (lhs.coefficients.is_empty(), rhs.coefficients.is_empty()) {
+                    (true, _) => rhs.scale(lhs.constant),
+                    (_, true) => lhs.scale(rhs.constant),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Add for Expr {
+    type Output = Expr;
+
+    fn add(self, rhs: Expr) -> Expr {
+        match (self, rhs) {
+            (Expr::Const(lhs), Expr::Const(rhs)) => Expr::Const(lhs.wrapping_add(rhs)),
+            (Expr::Const(0), other) | (other, Expr::Const(0)) => other,
+            (lhs, rhs) => Expr::Add(Box::new(lhs), Box::new(rhs)),
+        }
+    }
+}
+
+impl Mul for Expr {
+    type Output = Expr;
+
+    fn mul(self, rhs: Expr) -> Expr {
+        match (self, rhs) {
+            (Expr::Const(lhs), Expr::Const(rhs)) => Expr::Const(lhs.wrapping_mul(rhs)),
+            // even an unknown value times 0 is 0
+            (Expr::Const(0), _) | (_, Expr::Const(0)) => Expr::Const(0),
+            (Expr::Const(1), other) | (other, Expr::Const(1)) => other,
+            (lhs, rhs) => Expr::Mul(Box::new(lhs), Box::new(rhs)),
+        }
+    }
+}
+
+impl From<Int> for Expr {
+    fn from(value: Int) -> Expr {
+        Expr::Const(value)
+    }
+}
+
+impl From<Symbol> for Expr {
+    fn from(symbol: Symbol) -> Expr {
+        Expr::Symbol(symbol)
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Const(value) => write!(f, "{}", value),
+            Expr::Symbol(symbol) => write!(f, "{}", symbol),
+            Expr::Add(lhs, rhs) => write!(f, "({} + {})", lhs, rhs),
+            Expr::Mul(lhs, rhs) => write!(f, "({} * {})", lhs, rhs),
+            Expr::LessThan(lhs, rhs) => write!(f, "({} < {})", lhs, rhs),
+            Expr::Equals(lhs, rhs) => write!(f, "({} == {})", lhs, rhs),
+            Expr::Unknown => write!(f, "?"),
+        }
+    }
+}
+
+/// `constant + sum(coefficient * symbol)`, without any zero coefficients
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Linear {
+    pub constant: Int,
+    pub coefficients: BTreeMap<Symbol, Int>,
+}
+
+impl Linear {
+    fn add(&self, other: &Linear) -> Option<Linear> {
+        let mut sum = self.clone();
+        sum.constant = sum.constant.checked_add(other.constant)?;
+        for (symbol, coefficient) in &other.coefficients {
+            let entry = sum.coefficients.entry(*symbol).or_insert(0);
+            *entry = entry.checked_add(*coefficient)?;
+        }
+        sum.coefficients.retain(|_, coefficient| *coefficient != 0);
+
+        Some(sum)
+    }
+
+    fn scale(&self, factor: Int) -> Option<Linear> {
+        let mut product = Linear {
+            constant: self.constant.checked_mul(factor)?,
+            coefficients: BTreeMap::new(),
+        };
+        for (symbol, coefficient) in &self.coefficients {
+            product
+                .coefficients
+                .insert(*symbol, coefficient.checked_mul(factor)?);
+        }
+        product
+            .coefficients
+            .retain(|_, coefficient| *coefficient != 0);
+
+        Some(product)
+    }
+
+    /// The first values (in the order of `ranges`) that make the expression equal `target`, or
+    /// `None` if there aren't any or a symbol has no range.
+    /// Every symbol but one is tried in turn, the last one is worked out from the others.
+    pub fn solve(&self, target: Int, ranges: &[(Symbol, RangeInclusive<Int>)]) -> Option<Vec<Int>> {
+        if self
+            .coefficients
+            .keys()
+            .any(|symbol| !ranges.iter().any(|(s, _)| s == symbol))
+        {
+            return None;
+        }
+
+        // symbols after the solved one don't matter, so they're left at their first value
+        let solved = ranges
+            .iter()
+            .rposition(|(symbol, _)| self.coefficients.contains_key(symbol));
+        let tried = solved.unwrap_or(ranges.len());
+        let mut values = ranges.iter().map(|(_, range)| *range.start()).collect();
+        match self.solve_from(target, ranges, tried, 0, &mut values) {
+            true => Some(values),
+            false => None,
+        }
+    }
+
+    /// Try every value of `ranges[n]` (and the ones after it, up to `tried`)
+    fn solve_from(
+        &self,
+        target: Int,
+        ranges: &[(Symbol, RangeInclusive<Int>)],
+        tried: usize,
+        n: usize,
+        values: &mut Vec<Int>,
+    ) -> bool {
+        if n < tried {
+            for value in ranges[n].1.clone() {
+                values[n] = value;
+                if self.solve_from(target, ranges, tried, n + 1, values) {
+                    return true;
+                }
+            }
+            return false;
+        }
+
+        // the rest is `coefficient * solved = target - constant - everything that's been tried`
+        let mut rest = target as i128 - self.constant as i128;
+        for (i, (symbol, _)) in ranges[..tried].iter().enumerate() {
+            rest -= *self.coefficients.get(symbol).unwrap_or(&0) as i128 * values[i] as i128;
+        }
+        let (symbol, range) = match ranges.get(tried) {
+            Some(solved) => solved,
+            None => return rest == 0,
+        };
+        let coefficient = self.coefficients[symbol] as i128;
+        if rest % coefficient != 0 {
+            return false;
+        }
+
+        match Int::try_from(rest / coefficient) {
+            Ok(value) if range.contains(&value) => {
+                values[tried] = value;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Linear {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (symbol, coefficient)) in self.coefficients.iter().enumerate() {
+            let plus = if i == 0 { "" } else { " + " };
+            match coefficient {
+                1 => write!(f, "{}{}", plus, symbol)?,
+                _ => write!(f, "{}{} * {}", plus, coefficient, symbol)?,
+            }
+        }
+
+        match (self.constant, self.coefficients.is_empty()) {
+            (0, false) => Ok(()),
+            (constant, true) => write!(f, "{}", constant),
+            (constant, false) => write!(f, " + {}", constant),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SymbolicError {
+    Intcode(IntcodeError),
+    /// The program needs to know a value (e.g. an instruction, the address it writes to or whether
+    /// to jump) that depends on a symbol
+    NotConcrete {
+        ip: usize,
+        value: Expr,
+    },
+}
+
+impl fmt::Display for SymbolicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SymbolicError::Intcode(e) => write!(f, "{}", e),
+            SymbolicError::NotConcrete { ip, value } => {
+                write!(f, "{} isn't a constant at ip {}", value, ip)
+            }
+        }
+    }
+}
+
+impl Error for SymbolicError {}
+
+impl From<IntcodeError> for SymbolicError {
+    fn from(e: IntcodeError) -> SymbolicError {
+        SymbolicError::Intcode(e)
+    }
+}
+
+/// Memory as `OpCode::next` sees it, anything that isn't a constant is decoded as 0 (and looked
+/// at again once the instruction's shape is known)
+struct Shape<'a>(&'a HashMap<usize, Expr>);
+
+impl MemoryRead for Shape<'_> {
+    fn int_at(&self, address: usize) -> Option<Int> {
+        Some(self.0.get(&address).and_then(Expr::as_const).unwrap_or(0))
+    }
+}
+
+/// A parameter resolved against the machine's state
+enum Operand {
+    Value(Expr),
+    Address(usize),
+    /// An address that depends on a symbol
+    Symbolic(Expr),
+}
+
+/// An intcode machine that computes with `Expr`s
+#[derive(Debug, Clone)]
+pub struct Machine {
+    ip: usize,
+    rb: usize,
+    memory: HashMap<usize, Expr>,
+    input: VecDeque<Expr>,
+    /// How many symbolic inputs have been pushed
+    inputs: usize,
+    output: Vec<Expr>,
+}
+
+impl Machine {
+    pub fn new(ints: &[Int]) -> Machine {
+        Machine {
+            ip: 0,
+            rb: 0,
+            memory: ints
+                .iter()
+                .enumerate()
+                .map(|(address, value)| (address, Expr::Const(*value)))
+                .collect(),
+            input: VecDeque::new(),
+            inputs: 0,
+            output: vec![],
+        }
+    }
+
+    /// Make the memory cell at `address` a symbol, `Symbol::Memory(address)`
+    pub fn with_symbol(mut self, address: usize) -> Machine {
+        self.memory
+            .insert(address, Expr::Symbol(Symbol::Memory(address)));
+        self
+    }
+
+    pub fn push_input(&mut self, value: impl Into<Expr>) {
+        self.input.push_back(value.into());
+    }
+
+    /// Add a symbol to the input, the first one pushed is `Symbol::Input(0)` and so on
+    pub fn push_symbolic_input(&mut self) -> Symbol {
+        let symbol = Symbol::Input(self.inputs);
+        self.inputs += 1;
+        self.push_input(symbol);
+        symbol
+    }
+
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+
+    pub fn get(&self, address: usize) -> Expr {
+        self.memory.get(&address).cloned().unwrap_or(Expr::Const(0))
+    }
+
+    pub fn output(&self) -> &[Expr] {
+        &self.output
+    }
+
+    fn concrete(&self, value: Expr) -> Result<Int, SymbolicError> {
+        value
+            .as_const()
+            .ok_or(SymbolicError::NotConcrete { ip: self.ip, value })
+    }
+
+    fn operand(&self, parameter: &IntCode, raw: Expr) -> Result<Operand, AddressError> {
+        let offset = match (parameter, raw) {
+            (IntCode::Immediate(_), raw) => return Ok(Operand::Value(raw)),
+            (IntCode::Position(address), Expr::Const(_)) => return Ok(Operand::Address(*address)),
+            (IntCode::Relative(_), Expr::Const(offset)) => offset,
+            (_, raw) => return Ok(Operand::Symbolic(raw)),
+        };
+
+        match (self.rb as Int).checked_add(offset) {
+            Some(address) if address >= 0 => Ok(Operand::Address(address as usize)),
+            Some(address) => Err(AddressError::NegativeAddress(address)),
+            None => Err(AddressError::MemoryOutOfRange(usize::MAX)),
+        }
+    }
+
+    fn read(&self, operand: &Operand) -> Expr {
+        match operand {
+            Operand::Value(value) => value.clone(),
+            Operand::Address(address) => self.get(*address),
+            Operand::Symbolic(_) => Expr::Unknown,
+        }
+    }
+
+    /// Execute a single instruction, returns why the program stopped if it did
+    pub fn step(&mut self) -> Result<Option<StopReason>, SymbolicError> {
+        let start_ip = self.ip;
+        let instruction = self.concrete(self.get(start_ip))?;
+        let mut next = start_ip;
+        let op_code = OpCode::next(&mut next, &Shape(&self.memory))?;
+        let fail = |e: AddressError| e.at(start_ip, instruction);
+
+        let mut operands = vec![];
+        for (i, parameter) in op_code.parameters().iter().enumerate() {
+            let raw = self.get(start_ip + 1 + i);
+            operands.push(self.operand(parameter, raw).map_err(fail)?);
+        }
+        let mut values = operands.iter().map(|operand| self.read(operand));
+        let mut value = || values.next().unwrap_or(Expr::Unknown);
+
+        let mut jump = None;
+        let write = match op_code {
+            OpCode::Add { .. } => Some(value() + value()),
+            OpCode::Mult { .. } => Some(value() * value()),
+            OpCode::LessThan { .. } => Some(Expr::less_than(value(), value())),
+            OpCode::Equals { .. } => Some(Expr::equals(value(), value())),
+            OpCode::Input { .. } => match self.input.pop_front() {
+                Some(value) => Some(value),
+                None => return Ok(Some(StopReason::WaitingForInput)),
+            },
+            OpCode::Output { .. } => {
+                self.output.push(value());
+                None
+            }
+            OpCode::JumpIfTrue { .. } | OpCode::JumpIfFalse { .. } => {
+                let test = self.concrete(value())? != 0;
+                if test == matches!(op_code, OpCode::JumpIfTrue { .. }) {
+                    match self.concrete(value())? {
+                        destination if destination < 0 => {
+                            return Err(fail(AddressError::NegativeAddress(destination)).into())
+                        }
+                        destination => jump = Some(destination as usize),
+                    }
+                }
+                None
+            }
+            OpCode::AdjustRelativeBase { .. } => {
+                let amount = self.concrete(value())?;
+                self.rb = (self.rb as Int).wrapping_add(amount) as usize;
+                None
+            }
+            OpCode::Halt => {
+                self.ip = next;
+                return Ok(Some(StopReason::Halt));
+            }
+        };
+
+        if let Some(value) = write {
+            // the parameter that's written to is always the last one
+            match operands.pop() {
+                Some(Operand::Address(address)) => {
+                    self.memory.insert(address, value);
+                }
+                Some(Operand::Symbolic(address)) => {
+                    return Err(SymbolicError::NotConcrete {
+                        ip: start_ip,
+                        value: address,
+                    })
+                }
+                _ => return Err(fail(AddressError::WriteToImmediate).into()),
+            }
+        }
+        self.ip = jump.unwrap_or(next);
+
+        Ok(None)
+    }
+
+    /// Run until the program halts or needs more input, or for at most `budget` instructions
+    pub fn run(&mut self, budget: u64) -> Result<StopReason, SymbolicError> {
+        for _ in 0..budget {
+            if let Some(reason) = self.step()? {
+                return Ok(reason);
+            }
+        }
+
+        Ok(StopReason::BudgetExhausted)
+    }
+}
+
+/// Find the first values (in order) for the memory cells in `cells`, each within its range, that
+/// leave `target` at `address` once the program halts. It's solved algebraically if the result is
+/// linear in the cells, otherwise every combination is run with `search`.
+pub fn solve_memory(
+    ints: &[Int],
+    cells: &[(usize, RangeInclusive<Int>)],
+    address: usize,
+    target: Int,
+) -> Option<Vec<Int>> {
+    let halts_with_target = |values: &[Int]| {
+        let mut memory = ints.to_vec();
+        for ((cell, _), value) in cells.iter().zip(values) {
+            if memory.len() <= *cell {
+                memory.resize(cell + 1, 0);
+            }
+            memory[*cell] = *value;
+        }
+
+        let mut program = Program::new(memory);
+        program.run_no_io() == Ok(StopReason::Halt) && program.memory().get(address) == target
+    };
+
+    let mut machine = cells.iter().fold(Machine::new(ints), |machine, (cell, _)| {
+        machine.with_symbol(*cell)
+    });
+    if let Ok(StopReason::Halt) = machine.run(BUDGET) {
+        if let Some(linear) = machine.get(address).linear() {
+            let ranges = cells
+                .iter()
+                .map(|(cell, range)| (Symbol::Memory(*cell), range.clone()))
+                .collect::<Vec<_>>();
+            // the concrete run makes sure nothing overflowed along the way
+            match linear.solve(target, &ranges) {
+                Some(values) if halts_with_target(&values) => return Some(values),
+                Some(_) => {}
+                None => return None,
+            }
+        }
+    }
+
+    let ranges = cells
+        .iter()
+        .map(|(_, range)| range.clone())
+        .collect::<Vec<_>>();
+    search(&ranges, halts_with_target)
+}
+
+/// Try every combination of values (the first one from `ranges[0]` and so on) across every core,
+/// returns the first one (in order) that `test` accepts
+pub fn search<F>(ranges: &[RangeInclusive<Int>], test: F) -> Option<Vec<Int>>
+where
+    F: Fn(&[Int]) -> bool + Sync,
+{
+    let (first, rest) = match ranges.split_first() {
+        Some(split) => split,
+        None => return test(&[]).then(Vec::new),
+    };
+
+    let threads = thread::available_parallelism().map_or(1, usize::from);
+    // the first value of the best combination found so far, the other threads stop past it
+    let best = AtomicI64::new(Int::MAX);
+    thread::scope(|scope| {
+        let handles = (0..threads)
+            .map(|t| {
+                let (test, best) = (&test, &best);
+                scope.spawn(move || {
+                    for value in first.clone().skip(t).step_by(threads) {
+                        if value > best.load(Ordering::Relaxed) {
+                            return None;
+                        }
+
+                        let mut values = vec![value];
+                        if search_from(rest, test, &mut values) {
+                            best.fetch_min(value, Ordering::Relaxed);
+                            return Some(values);
+                        }
+                    }
+                    None
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().unwrap())
+            .min()
+    })
+}
+
+/// Try every combination of the values in `ranges` after `values`, leaving the first match in
+/// `values`
+fn search_from<F>(ranges: &[RangeInclusive<Int>], test: &F, values: &mut Vec<Int>) -> bool
+where
+    F: Fn(&[Int]) -> bool,
+{
+    let (first, rest) = match ranges.split_first() {
+        Some(split) => split,
+        None => return test(values),
+    };
+
+    for value in first.clone() {
+        values.push(value);
+        if search_from(rest, test, values) {
+            return true;
+        }
+        values.pop();
+    }
+
+    false
+}
@@ -0,0 +1,252 @@
+//! Opt-in tracing of every instruction a program executes, see `Program::run_traced`
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+
+use super::op_code::OpCode;
+use super::word::Word;
+use super::Int;
+
+/// A single executed instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step<W = Int> {
+    pub ip: usize,
+    pub op_code: OpCode<W>,
+    /// The values read for each parameter, `None` for the one that's written to
+    pub operands: [Option<W>; 3],
+    /// The address that was written to and the value written
+    pub write: Option<(usize, W)>,
+    pub input: Option<W>,
+    pub output: Option<W>,
+}
+
+pub trait Tracer<W = Int> {
+    /// Whether `trace` does anything at all, `Program` doesn't build any `Step`s when it doesn't
+    const ENABLED: bool = true;
+
+    fn trace(&mut self, step: &Step<W>);
+}
+
+/// Doesn't trace anything, what `Program::run` uses
+impl<W> Tracer<W> for () {
+    const ENABLED: bool = false;
+
+    #[inline]
+    fn trace(&mut self, _: &Step<W>) {}
+}
+
+impl<W, T: Tracer<W>> Tracer<W> for &mut T {
+    const ENABLED: bool = T::ENABLED;
+
+    fn trace(&mut self, step: &Step<W>) {
+        (**self).trace(step)
+    }
+}
+
+/// Trace with two tracers at once, e.g. `(&mut profile, &mut writer)`
+impl<W, A: Tracer<W>, B: Tracer<W>> Tracer<W> for (A, B) {
+    const ENABLED: bool = A::ENABLED || B::ENABLED;
+
+    fn trace(&mut self, step: &Step<W>) {
+        self.0.trace(step);
+        self.1.trace(step);
+    }
+}
+
+/// Counts of what a program spent its time on
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    pub steps: u64,
+    /// Executions per mnemonic
+    pub opcodes: HashMap<&'static str, u64>,
+    /// Executions per address, along with the instruction last executed there
+    pub addresses: HashMap<usize, (u64, OpCode)>,
+}
+
+impl Profile {
+    pub fn new() -> Profile {
+        Profile::default()
+    }
+
+    /// The `n` most executed addresses
+    pub fn hottest(&self, n: usize) -> Vec<(usize, u64, OpCode)> {
+        let mut addresses = self
+            .addresses
+            .iter()
+            .map(|(address, (count, op_code))| (*address, *count, *op_code))
+            .collect::<Vec<_>>();
+        addresses.sort_by_key(|(address, count, _)| (u64::MAX - count, *address));
+        addresses.truncate(n);
+
+        addresses
+    }
+}
+
+impl Tracer for Profile {
+    fn trace(&mut self, step: &Step) {
+        self.steps += 1;
+        *self.opcodes.entry(step.op_code.mnemonic()).or_default() += 1;
+        let entry = self.addresses.entry(step.ip).or_insert((0, step.op_code));
+        entry.0 += 1;
+        entry.1 = step.op_code;
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} steps", self.steps)?;
+
+        let mut opcodes = self.opcodes.iter().collect::<Vec<_>>();
+        opcodes.sort_by_key(|(mnemonic, count)| (u64::MAX - **count, **mnemonic));
+        writeln!(f, "\nopcode  count         share")?;
+        for (mnemonic, count) in opcodes {
+            let share = *count as f64 / self.steps.max(1) as f64 * 100.0;
+            writeln!(f, "{:<6}  {:<12}  {:>5.1}%", mnemonic, count, share)?;
+        }
+
+        writeln!(f, "\nhottest addresses")?;
+        for (address, count, op_code) in self.hottest(10) {
+            writeln!(f, "{:>5}  {:<12}  {}", address, count, op_code)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Keeps the first error a writer failed with, since `Tracer::trace` can't return one
+struct Sink<W: Write> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> Sink<W> {
+    fn write(&mut self, bytes: &[u8]) {
+        if self.error.is_none() {
+            if let Err(e) = self.writer.write_all(bytes) {
+                self.error = Some(e);
+            }
+        }
+    }
+
+    fn finish(mut self) -> io::Result<W> {
+        match self.error {
+            Some(e) => Err(e),
+            None => self.writer.flush().map(|_| self.writer),
+        }
+    }
+}
+
+/// Writes each step as a line of JSON:
+///
+/// ```text
+/// {"ip":0,"op":"add","operands":[1,2,null],"write":[5,3],"input":null,"output":null}
+/// ```
+pub struct JsonlTracer<W: Write> {
+    sink: Sink<W>,
+}
+
+impl<W: Write> JsonlTracer<W> {
+    pub fn new(writer: W) -> JsonlTracer<W> {
+        JsonlTracer {
+            sink: Sink {
+                writer,
+                error: None,
+            },
+        }
+    }
+
+    /// Flush the writer and return it, or the first error writing failed with
+    pub fn finish(self) -> io::Result<W> {
+        self.sink.finish()
+    }
+}
+
+fn json<V: Word>(value: &Option<V>) -> String {
+    value
+        .as_ref()
+        .map_or_else(|| String::from("null"), |v| v.to_string())
+}
+
+impl<V: Word, W: Write> Tracer<V> for JsonlTracer<W> {
+    fn trace(&mut self, step: &Step<V>) {
+        let operands = step.op_code.parameters().len();
+        let line = format!(
+            "{{\"ip\":{},\"op\":\"{}\",\"operands\":[{}],\"write\":{},\"input\":{},\"output\":{}}}\n",
+            step.ip,
+            step.op_code.mnemonic(),
+            step.operands[..operands]
+                .iter()
+                .map(json)
+                .collect::<Vec<_>>()
+                .join(","),
+            step.write
+                .as_ref()
+                .map_or_else(|| String::from("null"), |(a, v)| format!("[{},{}]", a, v)),
+            json(&step.input),
+            json(&step.output)
+        );
+        self.sink.write(line.as_bytes());
+    }
+}
+
+/// Writes each step as a compact binary record (all values little endian):
+///
+/// ```text
+/// ip: u64, opcode: u8, flags: u8, then an i64 for each operand that was read (flag bits 0-2),
+/// the address (u64) and value (i64) written (bit 3), the input (bit 4) and the output (bit 5)
+/// ```
+pub struct BinaryTracer<W: Write> {
+    sink: Sink<W>,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> BinaryTracer<W> {
+    pub fn new(writer: W) -> BinaryTracer<W> {
+        BinaryTracer {
+            sink: Sink {
+                writer,
+                error: None,
+            },
+            buffer: Vec::with_capacity(64),
+        }
+    }
+
+    /// Flush the writer and return it, or the first error writing failed with
+    pub fn finish(self) -> io::Result<W> {
+        self.sink.finish()
+    }
+}
+
+impl<W: Write> Tracer for BinaryTracer<W> {
+    fn trace(&mut self, step: &Step) {
+        let mut flags = 0;
+        let mut values = vec![];
+        for (i, operand) in step.operands.iter().enumerate() {
+            if let Some(operand) = operand {
+                flags |= 1 << i;
+                values.push(operand.to_le_bytes());
+            }
+        }
+        if let Some((address, value)) = step.write {
+            flags |= 1 << 3;
+            values.push((address as u64).to_le_bytes());
+            values.push(value.to_le_bytes());
+        }
+        for (bit, value) in [(4, step.input), (5, step.output)] {
+            if let Some(value) = value {
+                flags |= 1 << bit;
+                values.push(value.to_le_bytes());
+            }
+        }
+
+        self.buffer.clear();
+        self.buffer.extend((step.ip as u64).to_le_bytes());
+        self.buffer.push(step.op_code.code() as u8);
+        self.buffer.push(flags);
+        for value in values {
+            self.buffer.extend(value);
+        }
+        self.sink.write(&self.buffer);
+    }
+}
@@ -0,0 +1,189 @@
+// The assembler: encoding, labels, macros, listings and errors.
+
+use _2019::asm::{self, AsmError};
+use _2019::{disasm, Int, Program, StopReason};
+
+fn assemble(source: &str) -> Vec<Int> {
+    asm::assemble(source).unwrap().ints
+}
+
+/// The line and message of the error assembling `source`
+fn error(source: &str) -> (usize, String) {
+    let AsmError { line, message } = asm::assemble(source).unwrap_err();
+    (line, message)
+}
+
+#[test]
+fn encodes_modes_labels_and_data() {
+    let ints = assemble(
+        "
+        start:  add [x], #-1, rb+2  ; a comment
+                jt #1, #end+1
+        x:      data 7, \"a;b\", start
+        end:    halt
+        ",
+    );
+    assert_eq!(ints, [21001, 7, -1, 2, 1105, 1, 13, 7, 97, 59, 98, 0, 99]);
+
+    // the disassembler's listing uses the same syntax
+    let listing = disasm::listing(&ints);
+    assert!(listing.contains("add [7], #-1, rb+2"));
+    assert!(listing.contains("jt #1, #13"));
+}
+
+#[test]
+fn macros_expand_with_unique_labels() {
+    let source = "
+        .macro push value
+            add \\value, #0, rb+0
+            arb #1
+        .endmacro
+        .macro double value    ; invokes `push`
+            push \\value
+            push \\value
+        .endmacro
+        .macro countdown n
+        loop\\@:    out \\n
+                    add \\n, #-1, \\n
+                    jt \\n, #loop\\@
+        .endmacro
+
+                arb #100
+        twice:  double #5
+                countdown [a]
+                countdown [b]
+                out rb-1
+                halt
+        a:      data 2
+        b:      data 1
+        ";
+    let assembly = asm::assemble(source).unwrap();
+    assert_eq!(assembly.labels["twice"], 2);
+    // `double` and its two `push`es are the first three expansions
+    assert!(assembly.labels.contains_key("loop4"));
+    assert!(assembly.labels.contains_key("loop5"));
+
+    let mut output = vec![];
+    let reason = Program::new(assembly.ints).run(None, &mut output);
+    assert_eq!(reason, Ok(StopReason::Halt));
+    assert_eq!(output, [2, 1, 1, 5]);
+}
+
+#[test]
+fn every_instruction_is_encoded_with_its_opcode() {
+    let ints = assemble(
+        "
+        add [0], [1], [2]
+        mult [0], [1], [2]
+        in [0]
+        out [0]
+        jt [0], [1]
+        jf [0], [1]
+        lt [0], [1], [2]
+        eq [0], [1], [2]
+        arb [0]
+        halt
+        ",
+    );
+    assert_eq!(
+        ints,
+        [1, 0, 1, 2, 2, 0, 1, 2, 3, 0, 4, 0, 5, 0, 1, 6, 0, 1, 7, 0, 1, 2, 8, 0, 1, 2, 9, 0, 99]
+    );
+}
+
+#[test]
+fn macro_parameters_can_prefix_each_other() {
+    let ints = assemble(
+        "
+        .macro m a, ab
+            out \\ab
+            out \\a
+        .endmacro
+        m #1, #2
+        halt
+        ",
+    );
+    assert_eq!(ints, [104, 2, 104, 1, 99]);
+}
+
+#[test]
+fn listings_point_at_the_source() {
+    let assembly = asm::assemble(
+        "
+        .macro twice value
+            out \\value
+            out \\value
+        .endmacro
+        twice #3
+        halt
+        ",
+    )
+    .unwrap();
+
+    let lines = assembly
+        .listing()
+        .lines()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("    0  104,3 "));
+    assert!(lines[0].ends_with("6 | twice #3"));
+    assert!(lines[1].starts_with("    2  104,3 "));
+    assert!(lines[2].ends_with("7 | halt"));
+}
+
+#[test]
+fn reports_errors_with_their_line() {
+    let cases = [
+        ("halt\nfoo [1]", 2, "unknown instruction 'foo'"),
+        ("out #1, #2", 1, "'out' takes 1 parameters but got 2"),
+        (
+            "in #1",
+            1,
+            "'in' writes to its parameter 0, so it can't be immediate",
+        ),
+        (
+            "out 1",
+            1,
+            "'1' needs a parameter mode: [position], #immediate or rb+offset",
+        ),
+        ("\n\nout [missing]", 3, "unknown label 'missing'"),
+        ("a: halt\na: halt", 2, "label 'a' is defined more than once"),
+        ("out [-1]", 1, "negative address -1"),
+        (
+            "data 9223372036854775807+1",
+            1,
+            "'9223372036854775807+1' overflows",
+        ),
+        (
+            "data -9223372036854775807-2",
+            1,
+            "'-9223372036854775807-2' overflows",
+        ),
+        (".macro m\nhalt", 1, "macro 'm' is never ended"),
+        (".endmacro", 1, "'.endmacro' without a '.macro'"),
+        (
+            ".macro m a b\n.endmacro",
+            1,
+            "invalid macro parameter 'a b'",
+        ),
+        (".macro m a,\n.endmacro", 1, "invalid macro parameter ''"),
+        (
+            ".macro m a\n.endmacro\nm",
+            3,
+            "macro 'm' takes 1 arguments but got 0",
+        ),
+        (
+            ".macro m\nm\n.endmacro\n\nm",
+            5,
+            "macro 'm' is nested too deeply, does it invoke itself?",
+        ),
+    ];
+
+    for (source, line, message) in cases {
+        assert_eq!(error(source), (line, message.to_string()), "{:?}", source);
+    }
+
+    // the most negative int can still be written out
+    assert_eq!(assemble("data -9223372036854775808"), [Int::MIN]);
+}
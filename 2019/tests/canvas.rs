@@ -0,0 +1,125 @@
+// Programs that draw on a canvas, and ones that read what's been drawn.
+
+use std::cell::RefCell;
+
+use _2019::io::canvas::{Canvas, Direction, Robot, Screen, TextScreen};
+use _2019::{asm, Int, Program, StopReason};
+
+/// A program that writes each of `values` then halts
+fn writes(values: &[Int]) -> Program {
+    let mut ints = values
+        .iter()
+        .flat_map(|&value| [104, value])
+        .collect::<Vec<_>>();
+    ints.push(99);
+    Program::new(ints)
+}
+
+fn tiles(tile: Option<&Int>) -> char {
+    match tile {
+        Some(1) => '#',
+        Some(_) => '.',
+        None => ' ',
+    }
+}
+
+#[test]
+fn screens_draw_triples() {
+    let mut program = writes(&[0, 0, 1, 2, 1, 0, -1, 0, 42, 2, 1, 2, 1, 1, 1]);
+    let screen = Screen::new();
+    assert_eq!(program.run(None, &screen), Ok(StopReason::Halt));
+
+    let canvas = screen.into_canvas();
+    assert_eq!(canvas.get((-1, 0)), Some(&42));
+    assert_eq!(canvas.bounds(), Some(((-1, 0), (2, 1))));
+    assert_eq!(canvas.find(&2), Some((2, 1)));
+    assert_eq!(canvas.count(&1), 2);
+    assert_eq!(canvas.render(tiles), ".#  \n  #.\n");
+
+    let mut terminal = vec![];
+    canvas.draw(&mut terminal, tiles).unwrap();
+    assert_eq!(terminal, b"\x1b[H\x1b[2J.#  \n  #.\n");
+    assert_eq!(Canvas::<Int>::new().render(tiles), "");
+}
+
+#[test]
+fn joysticks_read_the_screen() {
+    let ints = asm::assemble(
+        "
+        loop:   in [x]
+                out [x]
+                out #0
+                out #1
+                add [n], #-1, [n]
+                jt [n], #loop
+                halt
+        x:      data 0
+        n:      data 3
+        ",
+    )
+    .unwrap()
+    .ints;
+    let mut program = Program::new(ints);
+    let screen = Screen::new();
+
+    // each tile is drawn two to the right of the last one
+    let input = screen.input(|canvas| Some(2 * canvas.len() as Int));
+    assert_eq!(program.run(input, &screen), Ok(StopReason::Halt));
+    assert_eq!(screen.canvas().render(tiles), "# # #\n");
+}
+
+#[test]
+fn text_screens_draw_lines() {
+    let text = "a\n.c\n".bytes().map(Int::from).chain([1000]);
+    let mut program = writes(&text.collect::<Vec<_>>());
+    let screen = TextScreen::new();
+    assert_eq!(program.run(None, &screen), Ok(StopReason::Halt));
+
+    assert_eq!(screen.canvas().to_string(), "a \n.c\n");
+    assert_eq!(screen.canvas().find(&'c'), Some((1, 1)));
+    assert_eq!(*screen.values(), [1000]);
+
+    screen.clear();
+    let mut program = writes(&[b'x'.into()]);
+    assert_eq!(program.run(None, &screen), Ok(StopReason::Halt));
+    assert_eq!(screen.canvas().to_string(), "x\n");
+}
+
+#[test]
+fn robots_paint_as_they_go() {
+    // the example from 2019-11, reading the camera before each move
+    let moves = [(1, 0), (0, 0), (1, 0), (1, 0), (0, 1), (1, 0), (1, 0)];
+    let mut ints = moves
+        .iter()
+        .flat_map(|&(colour, turn)| [3, 0, 104, colour, 104, turn])
+        .collect::<Vec<_>>();
+    ints.push(99);
+    let mut program = Program::new(ints);
+
+    let robot = Robot::new();
+    let seen = RefCell::new(vec![]);
+    let input = robot.input(|canvas, position| {
+        let colour = canvas.get(position).copied().unwrap_or(0);
+        seen.borrow_mut().push(colour);
+        Some(colour)
+    });
+    assert_eq!(program.run(input, &robot), Ok(StopReason::Halt));
+
+    assert_eq!(seen.into_inner(), [0, 0, 0, 0, 1, 0, 0]);
+    assert_eq!(robot.position(), (0, -1));
+    assert_eq!(robot.facing(), Direction::Left);
+    assert_eq!(robot.canvas().len(), 6);
+    assert_eq!(robot.canvas().render(tiles), "  #\n..#\n## \n");
+
+    // starting on a white tile
+    let mut canvas = Canvas::new();
+    canvas.insert((0, 0), 1);
+    let robot = Robot::with_canvas(canvas);
+    let mut program = Program::new(vec![3, 9, 4, 9, 104, 0, 99]);
+    let mut output = vec![];
+    assert_eq!(
+        program.run(robot.camera(), &mut output),
+        Ok(StopReason::Halt)
+    );
+    assert_eq!(output, [1, 0]);
+}
@@ -0,0 +1,125 @@
+// Control-flow graphs of small assembled programs.
+
+use _2019::asm;
+use _2019::cfg::{Exit, Graph, SelfModification};
+use _2019::Int;
+
+fn assemble(source: &str) -> Vec<Int> {
+    asm::assemble(source).unwrap().ints
+}
+
+fn exits(graph: &Graph) -> Vec<(usize, Exit)> {
+    graph
+        .blocks
+        .values()
+        .map(|block| (block.start, block.exit))
+        .collect()
+}
+
+#[test]
+fn branches_and_loops() {
+    let ints = assemble(
+        "
+        start:  in [x]
+        loop:   jf [x], #done
+                out [x]
+                add [x], #-1, [x]
+                jt #1, #loop
+        done:   halt
+        x:      data 0
+        ",
+    );
+    let graph = Graph::new(&ints);
+
+    assert_eq!(
+        exits(&graph),
+        [
+            (0, Exit::Fallthrough(2)),
+            (
+                2,
+                Exit::Branch {
+                    taken: 14,
+                    fallthrough: 5
+                }
+            ),
+            (5, Exit::Jump(2)),
+            (14, Exit::Halt),
+        ]
+    );
+    assert_eq!(graph.blocks[&5].lines.len(), 3);
+    assert!(graph.self_modifications.is_empty());
+    assert!(graph.to_dot().contains("b2 -> b14 [label=\"taken\"]"));
+}
+
+#[test]
+fn calls_and_returns() {
+    let ints = assemble(
+        "
+                arb #100
+                add #back, #0, rb+0
+                jt #1, #double
+        back:   out [x]
+                halt
+        double: mult [x], #2, [x]
+                jt #1, rb+0
+        x:      data 21
+        ",
+    );
+    let graph = Graph::new(&ints);
+
+    assert_eq!(
+        exits(&graph),
+        [
+            (
+                0,
+                Exit::Call {
+                    callee: 12,
+                    returns_to: 9
+                }
+            ),
+            (9, Exit::Halt),
+            (12, Exit::Return { fallthrough: None }),
+        ]
+    );
+    assert_eq!(graph.functions().into_iter().collect::<Vec<_>>(), [12]);
+    assert!(graph.to_string().contains("function 12..19:"));
+}
+
+#[test]
+fn flags_writes_into_code() {
+    // 2019-5 starts by patching the instruction it's about to run
+    let ints = [3, 225, 1, 225, 6, 6, 1100, 1, 238, 225, 104, 0, 99];
+    let graph = Graph::new(&ints);
+
+    assert_eq!(
+        graph.self_modifications,
+        [SelfModification {
+            ip: 2,
+            address: 6,
+            instruction: 6,
+        }]
+    );
+    assert_eq!(graph.blocks[&6].exit, Exit::Invalid);
+
+    // a write into the middle of an instruction points at the instruction
+    let ints = [1101, 1, 2, 9, 1105, 1, 8, 99, 104, 0, 99];
+    let graph = Graph::new(&ints);
+    assert_eq!(
+        graph.self_modifications,
+        [SelfModification {
+            ip: 0,
+            address: 9,
+            instruction: 8,
+        }]
+    );
+}
+
+#[test]
+fn jumps_into_the_middle_of_an_instruction() {
+    // the jump's own destination decodes as `mult [1105], [0], [0]`
+    let ints = [1105, 1, 2, 1105, 0, 0, 99];
+    let graph = Graph::new(&ints);
+
+    assert_eq!(exits(&graph), [(0, Exit::Jump(2)), (2, Exit::Halt)]);
+    assert_eq!(graph.blocks[&2].end(), 7);
+}
@@ -0,0 +1,219 @@
+// The example programs from the puzzles that describe the intcode computer (days 2, 5 and 9), and
+// the puzzle inputs run by `Compiled`.
+
+use std::collections::VecDeque;
+
+use _2019::compile::Compiled;
+use _2019::trace::Profile;
+use _2019::{ints_from_str, Int, IntcodeError, Program, StopReason};
+
+/// Run a program to completion with some input, returns its output and final memory
+fn run(ints: &[Int], input: &[Int]) -> (Vec<Int>, Vec<Int>) {
+    let mut program = Program::new(ints.to_vec());
+    let mut output = vec![];
+    let reason = program
+        .run(VecDeque::from(input.to_vec()), &mut output)
+        .unwrap();
+    assert_eq!(reason, StopReason::Halt);
+
+    (output, program.get_memory())
+}
+
+#[test]
+fn day_2_add_and_mult() {
+    let cases: [(&[Int], &[Int]); 5] = [
+        (&[1, 0, 0, 0, 99], &[2, 0, 0, 0, 99]),
+        (&[2, 3, 0, 3, 99], &[2, 3, 0, 6, 99]),
+        (&[2, 4, 4, 5, 99, 0], &[2, 4, 4, 5, 99, 9801]),
+        (
+            &[1, 1, 1, 4, 99, 5, 6, 0, 99],
+            &[30, 1, 1, 4, 2, 5, 6, 0, 99],
+        ),
+        (
+            &[1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50],
+            &[3500, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50],
+        ),
+    ];
+
+    for (ints, expected) in cases {
+        assert_eq!(run(ints, &[]).1, expected, "{:?}", ints);
+    }
+}
+
+#[test]
+fn day_5_io_and_modes() {
+    assert_eq!(run(&[3, 0, 4, 0, 99], &[42]).0, [42]);
+    assert_eq!(run(&[1002, 4, 3, 4, 33], &[]).1, [1002, 4, 3, 4, 99]);
+    assert_eq!(run(&[1101, 100, -1, 4, 0], &[]).1, [1101, 100, -1, 4, 99]);
+}
+
+#[test]
+fn day_5_comparisons() {
+    let equal_position = [3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8];
+    let less_position = [3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8];
+    let equal_immediate = [3, 3, 1108, -1, 8, 3, 4, 3, 99];
+    let less_immediate = [3, 3, 1107, -1, 8, 3, 4, 3, 99];
+
+    for input in [-3, 0, 7, 8, 9, 100] {
+        let equal = Int::from(input == 8);
+        let less = Int::from(input < 8);
+        assert_eq!(run(&equal_position, &[input]).0, [equal]);
+        assert_eq!(run(&less_position, &[input]).0, [less]);
+        assert_eq!(run(&equal_immediate, &[input]).0, [equal]);
+        assert_eq!(run(&less_immediate, &[input]).0, [less]);
+    }
+}
+
+#[test]
+fn day_5_jumps() {
+    let position = [3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9];
+    let immediate = [3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1];
+
+    for input in [-1, 0, 1, 5] {
+        let expected = Int::from(input != 0);
+        assert_eq!(run(&position, &[input]).0, [expected]);
+        assert_eq!(run(&immediate, &[input]).0, [expected]);
+    }
+}
+
+#[test]
+fn day_5_compare_to_eight() {
+    let ints = [
+        3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0, 0,
+        1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4, 20,
+        1105, 1, 46, 98, 99,
+    ];
+
+    assert_eq!(run(&ints, &[7]).0, [999]);
+    assert_eq!(run(&ints, &[8]).0, [1000]);
+    assert_eq!(run(&ints, &[9]).0, [1001]);
+}
+
+#[test]
+fn day_9_quine() {
+    let ints = [
+        109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+    ];
+
+    assert_eq!(run(&ints, &[]).0, ints);
+}
+
+#[test]
+fn day_9_large_numbers() {
+    assert_eq!(
+        run(&[1102, 34915192, 34915192, 7, 4, 7, 99, 0], &[]).0,
+        [1219070632396864]
+    );
+    assert_eq!(run(&[104, 1125899906842624, 99], &[]).0, [1125899906842624]);
+}
+
+#[test]
+fn waits_for_input_and_resumes() {
+    let mut program = Program::new(vec![3, 0, 4, 0, 99]);
+    let mut input = VecDeque::new();
+    let mut output = vec![];
+    let reason = program.run(&mut input, &mut output).unwrap();
+    assert_eq!(reason, StopReason::WaitingForInput);
+    assert_eq!(program.ip(), 0);
+
+    input.push_back(7);
+    let reason = program.run(&mut input, &mut output).unwrap();
+    assert_eq!(reason, StopReason::Halt);
+    assert_eq!(output, [7]);
+}
+
+#[test]
+fn runs_instructions_far_past_the_program() {
+    // jumps to an instruction it stored a billion ints past its end
+    let ints = vec![1101, 99, 0, 1_000_000_000, 1105, 1, 1_000_000_000];
+    for cache in [true, false] {
+        let mut program = Program::new(ints.clone()).with_decode_cache(cache);
+        assert_eq!(program.run_no_io(), Ok(StopReason::Halt));
+        assert_eq!(program.ip(), 1_000_000_001);
+    }
+}
+
+#[test]
+fn errors_report_the_instruction() {
+    let mut program = Program::new(vec![1101, 1, 1, 5, 42, 0]);
+    let error = program.run_no_io().unwrap_err();
+    assert!(matches!(error, IntcodeError::InvalidOpcode { ip: 4, .. }));
+
+    let mut program = Program::new(vec![1, 0, 0, 0, 11101, 1, 1, 0, 99]);
+    let error = program.run_no_io().unwrap_err();
+    assert!(matches!(
+        error,
+        IntcodeError::WriteToImmediate { ip: 4, .. }
+    ));
+
+    let mut program = Program::new(vec![Int::MIN]);
+    let error = program.run_no_io().unwrap_err();
+    assert!(matches!(error, IntcodeError::InvalidOpcode { ip: 0, .. }));
+}
+
+#[test]
+fn failing_instructions_are_traced() {
+    // the second instruction writes to rb-1
+    let mut program = Program::new(vec![1101, 1, 2, 9, 21101, 1, 1, -1, 99, 0]);
+    let mut profile = Profile::new();
+    let error = program
+        .run_traced(None, &mut vec![], &mut profile)
+        .unwrap_err();
+    assert!(matches!(error, IntcodeError::NegativeAddress { ip: 4, .. }));
+    assert_eq!(profile.steps, 2);
+    assert_eq!(profile.addresses[&4].0, 1);
+
+    // but a read that has to wait for input isn't a step
+    let mut program = Program::new(vec![3, 0, 99]);
+    let mut profile = Profile::new();
+    let reason = program.run_traced(None, &mut vec![], &mut profile);
+    assert_eq!(reason, Ok(StopReason::WaitingForInput));
+    assert_eq!(profile.steps, 0);
+}
+
+#[test]
+fn compiled_programs_match_the_interpreter() {
+    let cases = [
+        (include_str!("../examples/input/2019-05.txt"), 5),
+        (include_str!("../examples/input/2019-07.txt"), 4),
+        (include_str!("../examples/input/2019-09.txt"), 1),
+    ];
+
+    for (input, value) in cases {
+        let ints = ints_from_str(input.trim());
+        let mut expected = vec![];
+        Program::new(ints.clone())
+            .run(VecDeque::from([value, 0]), &mut expected)
+            .unwrap();
+
+        let mut compiled = Compiled::new(ints);
+        let mut output = vec![];
+        let reason = compiled
+            .run(VecDeque::from([value, 0]), &mut output)
+            .unwrap();
+        assert_eq!(reason, StopReason::Halt);
+        assert_eq!(output, expected);
+    }
+
+    // 2019-5 writes over instructions it's already run
+    let mut compiled = Compiled::new(ints_from_str(
+        include_str!("../examples/input/2019-05.txt").trim(),
+    ));
+    compiled.run(VecDeque::from([5]), &mut vec![]).unwrap();
+    assert!(compiled.invalidated() > 0);
+
+    // the last instruction starts at the last of the original ints, and its parameter (past them)
+    // is written after it's first run
+    let ints = vec![
+        1101, 1105, 0, 28, 1101, 1, 0, 29, 1101, 15, 0, 30, 1105, 1, 26, 1101, 42, 0, 27, 1101, 99,
+        0, 28, 1105, 1, 26, 104,
+    ];
+    let mut expected = vec![];
+    Program::new(ints.clone()).run(None, &mut expected).unwrap();
+    assert_eq!(expected, [0, 42]);
+
+    let mut output = vec![];
+    let reason = Compiled::new(ints).run(None, &mut output).unwrap();
+    assert_eq!(reason, StopReason::Halt);
+    assert_eq!(output, expected);
+}
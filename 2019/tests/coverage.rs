@@ -0,0 +1,111 @@
+// Coverage and write logs of running programs, and diffs of their memory.
+
+use std::collections::{BTreeSet, VecDeque};
+
+use _2019::coverage::MemoryWrite;
+use _2019::diff::{Change, Diff};
+use _2019::{ints_from_str, Program, StopReason};
+
+#[test]
+fn amplifiers_run_different_code_per_phase() {
+    let ints = ints_from_str(include_str!("../examples/input/2019-07.txt").trim());
+    let mut paths = BTreeSet::new();
+
+    for phase in 0..5 {
+        let mut program = Program::new(ints.clone()).with_coverage(true);
+        let mut output = vec![];
+        let reason = program.run(VecDeque::from([phase, 0]), &mut output);
+        assert_eq!(reason, Ok(StopReason::Halt));
+
+        // the phase picks an entry from a jump table, then the signal is stored next to it
+        let diff = Diff::new(&ints, &program.get_memory());
+        assert_eq!(
+            diff.changes,
+            [
+                Change {
+                    address: 8,
+                    before: 0,
+                    after: phase + 10,
+                },
+                Change {
+                    address: 9,
+                    before: 0,
+                    after: output[0],
+                },
+            ]
+        );
+
+        let coverage = program.coverage().unwrap();
+        assert_eq!(
+            coverage.writes()[..2],
+            [
+                MemoryWrite {
+                    ip: 0,
+                    address: 8,
+                    value: phase,
+                },
+                MemoryWrite {
+                    ip: 2,
+                    address: 8,
+                    value: phase + 10,
+                },
+            ]
+        );
+        assert!(coverage.is_executed(6) && !coverage.is_executed(7));
+        assert!(coverage.listing(&ints).starts_with(">     0  3,8 "));
+        paths.insert(coverage.executed().collect::<Vec<_>>());
+    }
+
+    assert_eq!(paths.len(), 5);
+}
+
+#[test]
+fn coverage_is_off_by_default() {
+    let mut program = Program::new(vec![1101, 1, 2, 5, 99, 0]);
+    assert_eq!(program.run_no_io(), Ok(StopReason::Halt));
+    assert!(program.coverage().is_none());
+    assert!(program.take_coverage().is_none());
+
+    let mut program = Program::new(vec![1101, 1, 2, 5, 99, 0]).with_coverage(true);
+    assert_eq!(program.run_no_io(), Ok(StopReason::Halt));
+    let coverage = program.take_coverage().unwrap();
+    assert_eq!(coverage.executed().collect::<Vec<_>>(), [0, 4]);
+    assert_eq!(coverage.writes().len(), 1);
+
+    // tracking starts again from scratch
+    assert_eq!(program.coverage().unwrap().executed().count(), 0);
+}
+
+#[test]
+fn code_far_out_in_memory_is_covered() {
+    // writes a halt far past the program, then jumps to it
+    let far = 1_000_000_000_000;
+    let mut program = Program::new(vec![1101, 99, 0, far, 1105, 1, far]).with_coverage(true);
+    assert_eq!(program.run_no_io(), Ok(StopReason::Halt));
+
+    let coverage = program.coverage().unwrap();
+    assert_eq!(
+        coverage.executed().collect::<Vec<_>>(),
+        [0, 4, far as usize]
+    );
+    assert!(coverage.is_executed(far as usize) && !coverage.is_executed(far as usize + 1));
+}
+
+#[test]
+fn diffs_between_snapshots() {
+    let before = Program::new(vec![1, 0, 0, 0, 99]);
+    let mut after = before.clone();
+    assert_eq!(after.run_no_io(), Ok(StopReason::Halt));
+    let mut after = after.snapshot();
+    after.memory.set(100_000, 7);
+
+    let diff = Diff::between(&before.snapshot(), &after);
+    assert_eq!(
+        diff.to_string(),
+        "    0  1,0,0,0                   add [0], [0], [0]\n\
+         \x20        [0] 1 -> 2\n\
+         past the end of the program:\n\
+         \x20        [100000] 0 -> 7\n"
+    );
+    assert!(Diff::between(&after, &after).is_empty());
+}
@@ -0,0 +1,128 @@
+// The debugger's REPL, driven by scripted commands.
+
+use _2019::debugger::{Debugger, Event};
+use _2019::{ints_from_str, Program, StopReason};
+
+/// Outputs 1 if its input is 8, otherwise 0 (from 2019-05)
+const EQUALS_8: &str = "3,9,8,9,10,9,4,9,99,-1,8";
+
+/// Everything the REPL prints running `script`, a line per prompt
+fn repl(program: &mut Program, script: &str) -> Vec<String> {
+    let mut out = vec![];
+    Debugger::new(program)
+        .repl(script.as_bytes(), &mut out)
+        .unwrap();
+    String::from_utf8(out)
+        .unwrap()
+        .split("(intcode) ")
+        .map(|s| s.trim_end().to_string())
+        .collect()
+}
+
+#[test]
+fn breakpoints_stop_the_program() {
+    let mut program = Program::new(ints_from_str(EQUALS_8));
+    let transcript = repl(&mut program, "b 6\nb 8\nb\ni 8\nc\nr\ndb 6\ndb 6\nc\nc\nc");
+
+    assert_eq!(transcript[3], "breakpoints: 6, 8");
+    assert_eq!(
+        transcript[5],
+        "breakpoint at 6\n=>     6  4,9                       out [9]"
+    );
+    assert_eq!(transcript[6], "ip 6  rb 0");
+    assert_eq!(transcript[8], "no breakpoint at 6");
+    assert!(transcript[9].starts_with("output: 1\nbreakpoint at 8\n"));
+    assert_eq!(transcript[10], "halted");
+    // halting is sticky, rather than running whatever follows the halt instruction
+    assert_eq!(transcript[11], "halted");
+    assert_eq!(program.ip(), 9);
+}
+
+#[test]
+fn watchpoints_stop_when_memory_changes() {
+    let mut program = Program::new(ints_from_str(EQUALS_8));
+    let transcript = repl(&mut program, "w 9\nw 10\nw\ni 7\nc\nc\ndw 10\ndw 10\nc");
+
+    assert_eq!(transcript[3], "watchpoints: 9, 10");
+    assert!(transcript[5].starts_with("watchpoint: [9] -1 -> 7\n=>     2  "));
+    assert!(transcript[6].starts_with("watchpoint: [9] 7 -> 0\n=>     6  "));
+    assert_eq!(transcript[8], "no watchpoint at 10");
+    assert_eq!(transcript[9], "output: 0\nhalted");
+
+    // the same through the API
+    let mut program = Program::new(ints_from_str(EQUALS_8));
+    let mut debugger = Debugger::new(&mut program);
+    debugger.add_watchpoint(9);
+    assert_eq!(
+        debugger.resume(),
+        Ok(Event::Stopped(StopReason::WaitingForInput))
+    );
+    debugger.push_input(Some(8));
+    let event = debugger.resume();
+    assert_eq!(
+        event,
+        Ok(Event::Watchpoint {
+            address: 9,
+            old: -1,
+            new: 8
+        })
+    );
+    assert!(debugger.remove_watchpoint(9));
+    assert_eq!(debugger.resume(), Ok(Event::Stopped(StopReason::Halt)));
+    assert_eq!(debugger.take_output(), [1]);
+}
+
+#[test]
+fn commands_are_parsed() {
+    let mut program = Program::new(ints_from_str(EQUALS_8));
+    let transcript = repl(
+        &mut program,
+        "\nbogus\nb x\ndb\ni 1,x\nia hi\nm 0 4\nl 8 1\nl 18446744073709551615\n\
+         m 18446744073709551614\ns\nm 9 1\nq\nr",
+    );
+
+    assert_eq!(
+        transcript[0],
+        "ip 0  rb 0\n=>     0  3,9                       in [9]"
+    );
+    assert_eq!(transcript[1], "");
+    assert_eq!(transcript[2], "unknown command 'bogus', try `help`");
+    assert_eq!(transcript[3], "error: 'x' isn't an address");
+    assert_eq!(transcript[4], "error: 'db' needs an address");
+    assert!(transcript[5].starts_with("error: invalid input: "));
+    assert_eq!(transcript[6], "");
+    assert_eq!(
+        transcript[7],
+        "    0:      3      9      8      9\n\
+         =>     0  3,9                       in [9]\n\
+         \x20      2  8,9                       data 8, 9"
+    );
+    assert_eq!(transcript[8], "       8  99                        halt");
+    // windows at the very end of memory are cut short instead of overflowing
+    assert_eq!(transcript[9], "");
+    assert!(transcript[10].starts_with("18446744073709551614:      0\n"));
+
+    // the bad `input` queued nothing, so the program reads the 'h' of the "hi\n" `ia` queued
+    assert!(transcript[11].starts_with("=>     2  8,9,10,9"));
+    assert!(transcript[12].starts_with("    9:    104\n"));
+    // nothing runs after `quit`
+    assert_eq!(transcript.len(), 14);
+}
+
+#[test]
+fn huge_windows_are_clamped() {
+    let mut program = Program::new(ints_from_str(EQUALS_8));
+    let transcript = repl(&mut program, "m 0 18446744073709551615\nl 0 1000000000");
+
+    // `mem` shows 8 values per row, then the disassembly of the same window
+    let rows = transcript[1]
+        .lines()
+        .filter(|line| line.contains(": "))
+        .collect::<Vec<_>>();
+    assert_eq!(rows.len(), 4096 / 8);
+    assert!(rows.last().unwrap().starts_with(" 4088: "));
+
+    // and `list` stops at the same address
+    let last = transcript[2].lines().last().unwrap();
+    assert_eq!(last, "    4094  0,0                       data 0, 0");
+}
@@ -0,0 +1,429 @@
+// Random (but valid) programs, run by `Program` (and `Compiled`) and by a deliberately simple
+// reference interpreter that both have to agree on. The programs can jump anywhere and overwrite
+// their own code, which exercises the decode cache and recompiling. Overflowing is an error for
+// both, as they're run in their checked mode.
+
+use std::collections::{HashMap, VecDeque};
+
+use _2019::compile::Compiled;
+use _2019::{Int, IntcodeError, Memory, Program, Snapshot, StopReason};
+
+/// How many programs each property is checked with
+const CASES: usize = 2000;
+/// How many instructions each program may run
+const BUDGET: u64 = 500;
+/// Where the data after a program's code starts, most parameters address it
+const DATA: usize = 64;
+/// How many ints of data follow the code
+const DATA_LEN: usize = 32;
+
+/// xorshift64*, so every run generates the same programs
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn range(&mut self, low: Int, high: Int) -> Int {
+        low + (self.next() % (high - low) as u64) as Int
+    }
+}
+
+/// Generate a program along with the input to give it
+fn generate(rng: &mut Rng) -> (Vec<Int>, Vec<Int>) {
+    let mut ints = vec![];
+    let mut starts = vec![];
+    for _ in 0..rng.below(12) + 3 {
+        starts.push(ints.len());
+
+        // the weights favour the instructions that do the most to memory
+        let (code, params) = match rng.below(20) {
+            0..=3 => (1, 3),
+            4..=6 => (2, 3),
+            7 => (3, 1),
+            8..=9 => (4, 1),
+            10..=11 => (5, 2),
+            12..=13 => (6, 2),
+            14..=15 => (7, 3),
+            16..=17 => (8, 3),
+            18 => (9, 1),
+            _ => (99, 0),
+        };
+        let writes = matches!(code, 1 | 2 | 3 | 7 | 8);
+
+        let mut instruction = code;
+        let mut values = vec![];
+        for param in 0..params {
+            let mode = match writes && param == params - 1 {
+                true => [0, 2][rng.below(2)],
+                false => rng.below(3) as Int,
+            };
+            instruction += mode * [100, 1000, 10000][param];
+            // now and then address the code instead of the data, so programs modify themselves
+            let base = match rng.below(8) {
+                0 => 0,
+                _ => DATA as Int,
+            };
+            values.push(match mode {
+                0 => base + rng.below(DATA_LEN) as Int,
+                // keep the relative base from wandering below zero too often
+                1 if code == 9 => rng.range(-3, 10),
+                1 => rng.range(-50, 50),
+                _ => base + rng.range(-2, 20),
+            });
+        }
+
+        ints.push(instruction);
+        ints.extend(values);
+    }
+
+    // point jumps with an immediate destination at the start of an instruction, so there are loops
+    for start in &starts {
+        let instruction = ints[*start];
+        if matches!(instruction % 100, 5 | 6) && instruction / 1000 % 10 == 1 {
+            ints[start + 2] = starts[rng.below(starts.len())] as Int;
+        }
+    }
+
+    // falling off the end halts, the data after it can still be jumped to or written over
+    ints.resize(DATA, 99);
+    ints.extend((0..DATA_LEN).map(|_| rng.range(-10, 100)));
+
+    let input = (0..rng.below(4)).map(|_| rng.range(-10, 10)).collect();
+    (ints, input)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Outcome {
+    Stopped(StopReason),
+    /// The program failed at this ip
+    Failed(usize),
+}
+
+/// A straightforward intcode interpreter, without any of `Program`'s structure
+struct Reference {
+    ip: usize,
+    rb: usize,
+    memory: HashMap<usize, Int>,
+}
+
+impl Reference {
+    fn new(ints: &[Int]) -> Reference {
+        Reference {
+            ip: 0,
+            rb: 0,
+            memory: ints.iter().copied().enumerate().collect(),
+        }
+    }
+
+    fn get(&self, address: usize) -> Int {
+        self.memory.get(&address).copied().unwrap_or(0)
+    }
+
+    /// The address of parameter `n` of the instruction at the ip, or `None` if it's immediate
+    fn address(&mut self, n: usize) -> Result<Option<usize>, ()> {
+        let value = self.get(self.ip + 1 + n);
+        let address = match self.get(self.ip) / [100, 1000, 10000][n] % 10 {
+            0 => value,
+            1 => return Ok(None),
+            _ => (self.rb as Int).checked_add(value).ok_or(())?,
+        };
+
+        match address < 0 {
+            true => Err(()),
+            false => Ok(Some(address as usize)),
+        }
+    }
+
+    fn read(&mut self, n: usize) -> Result<Int, ()> {
+        match self.address(n)? {
+            Some(address) => Ok(self.get(address)),
+            None => Ok(self.get(self.ip + 1 + n)),
+        }
+    }
+
+    fn write(&mut self, n: usize, value: Int) -> Result<(), ()> {
+        let address = self.address(n)?.ok_or(())?;
+        self.memory.insert(address, value);
+        Ok(())
+    }
+
+    fn step(
+        &mut self,
+        input: &mut Vec<Int>,
+        output: &mut Vec<Int>,
+    ) -> Result<Option<StopReason>, ()> {
+        let instruction = self.get(self.ip);
+        let params = match instruction % 100 {
+            _ if instruction < 0 => return Err(()),
+            1 | 2 | 7 | 8 => 3,
+            5 | 6 => 2,
+            3 | 4 | 9 => 1,
+            99 => 0,
+            _ => return Err(()),
+        };
+
+        // position parameters are checked when the instruction is decoded, before it runs
+        for n in 0..params {
+            let mode = instruction / [100, 1000, 10000][n] % 10;
+            if mode > 2 || (mode == 0 && self.get(self.ip + 1 + n) < 0) {
+                return Err(());
+            }
+        }
+
+        let next = self.ip + 1 + params;
+        match instruction % 100 {
+            1 => {
+                let sum = self.read(0)?.checked_add(self.read(1)?).ok_or(())?;
+                self.write(2, sum)?
+            }
+            2 => {
+                let product = self.read(0)?.checked_mul(self.read(1)?).ok_or(())?;
+                self.write(2, product)?
+            }
+            3 => match input.is_empty() {
+                true => return Ok(Some(StopReason::WaitingForInput)),
+                false => {
+                    let value = input.remove(0);
+                    self.write(0, value)?;
+                }
+            },
+            4 => {
+                let value = self.read(0)?;
+                output.push(value)
+            }
+            5 | 6 if (self.read(0)? != 0) == (instruction % 100 == 5) => {
+                let destination = self.read(1)?;
+                self.ip = usize::try_from(destination).map_err(|_| ())?;
+                return Ok(None);
+            }
+            7 => {
+                let less = Int::from(self.read(0)? < self.read(1)?);
+                self.write(2, less)?
+            }
+            8 => {
+                let equal = Int::from(self.read(0)? == self.read(1)?);
+                self.write(2, equal)?
+            }
+            9 => {
+                let rb = (self.rb as Int).checked_add(self.read(0)?).ok_or(())?;
+                self.rb = rb as usize;
+            }
+            99 => {
+                self.ip = next;
+                return Ok(Some(StopReason::Halt));
+            }
+            _ => {}
+        }
+
+        self.ip = next;
+        Ok(None)
+    }
+
+    fn run(&mut self, input: &mut Vec<Int>, output: &mut Vec<Int>, budget: u64) -> Outcome {
+        for _ in 0..budget {
+            match self.step(input, output) {
+                Ok(None) => {}
+                Ok(Some(reason)) => return Outcome::Stopped(reason),
+                Err(()) => return Outcome::Failed(self.ip),
+            }
+        }
+
+        Outcome::Stopped(StopReason::BudgetExhausted)
+    }
+}
+
+fn to_outcome(result: Result<StopReason, IntcodeError>) -> Outcome {
+    match result {
+        Ok(reason) => Outcome::Stopped(reason),
+        Err(e) => Outcome::Failed(e.ip()),
+    }
+}
+
+/// Run with `input` in a queue, leaving whatever wasn't read in `input`
+fn run(program: &mut Program, input: &mut Vec<Int>, output: &mut Vec<Int>, budget: u64) -> Outcome {
+    let mut queue = VecDeque::from(std::mem::take(input));
+    let outcome = to_outcome(program.run_with_budget(&mut queue, output, budget));
+    *input = queue.into();
+    outcome
+}
+
+/// Check that a program's memory and rb ended up the same as `reference`'s
+fn assert_same(case: usize, ints: &[Int], memory: &Memory, rb: usize, reference: &Reference) {
+    let addresses = reference
+        .memory
+        .keys()
+        .copied()
+        .chain(0..memory.dense().len());
+    for address in addresses {
+        assert_eq!(
+            memory.get(address),
+            reference.get(address),
+            "case {}: memory at {} differs for {:?}",
+            case,
+            address,
+            ints
+        );
+    }
+    assert_eq!(rb, reference.rb, "case {}: {:?}", case, ints);
+}
+
+#[test]
+fn program_matches_the_reference() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    for case in 0..CASES {
+        let (ints, input) = generate(&mut rng);
+
+        let mut reference = Reference::new(&ints);
+        let mut expected = vec![];
+        let outcome = reference.run(&mut input.clone(), &mut expected, BUDGET);
+
+        for cache in [true, false] {
+            let mut program = Program::new(ints.clone())
+                .with_decode_cache(cache)
+                .with_checked_arithmetic(true);
+            let mut output = vec![];
+            assert_eq!(
+                run(&mut program, &mut input.clone(), &mut output, BUDGET),
+                outcome,
+                "case {} (cache {}): {:?} with input {:?}",
+                case,
+                cache,
+                ints,
+                input
+            );
+            assert_eq!(output, expected, "case {}: {:?}", case, ints);
+            if !matches!(outcome, Outcome::Failed(_)) {
+                assert_eq!(program.ip(), reference.ip, "case {}: {:?}", case, ints);
+                assert_same(case, &ints, program.memory(), program.rb(), &reference);
+            }
+        }
+    }
+}
+
+#[test]
+fn compiled_matches_the_reference() {
+    let mut rng = Rng(0x6a09_e667_f3bc_c908);
+    for case in 0..CASES {
+        let (ints, input) = generate(&mut rng);
+
+        let mut reference = Reference::new(&ints);
+        let mut expected = vec![];
+        let outcome = reference.run(&mut input.clone(), &mut expected, BUDGET);
+
+        let mut compiled = Compiled::new(ints.clone()).with_checked_arithmetic(true);
+        let mut output = vec![];
+        assert_eq!(
+            to_outcome(compiled.run_with_budget(
+                VecDeque::from(input.clone()),
+                &mut output,
+                BUDGET
+            )),
+            outcome,
+            "case {}: {:?} with input {:?}",
+            case,
+            ints,
+            input
+        );
+        assert_eq!(output, expected, "case {}: {:?}", case, ints);
+        if !matches!(outcome, Outcome::Failed(_)) {
+            assert_eq!(compiled.ip(), reference.ip, "case {}: {:?}", case, ints);
+            assert_same(case, &ints, compiled.memory(), compiled.rb(), &reference);
+        }
+    }
+}
+
+#[test]
+fn snapshots_resume_where_they_left_off() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    for case in 0..CASES {
+        let (ints, input) = generate(&mut rng);
+        let split = rng.below(BUDGET as usize) as u64;
+
+        let mut whole = Program::new(ints.clone());
+        let mut expected = vec![];
+        let outcome = run(&mut whole, &mut input.clone(), &mut expected, BUDGET);
+
+        // run part of the way, then carry on from a snapshot that's been through JSON
+        let mut first = Program::new(ints.clone());
+        let mut rest = input.clone();
+        let mut output = vec![];
+        let outcome = match run(&mut first, &mut rest, &mut output, split) {
+            Outcome::Stopped(StopReason::BudgetExhausted) => {
+                let snapshot = first.snapshot().with_io(rest, vec![]);
+                let json = serde_json::to_string(&snapshot).unwrap();
+                let snapshot = serde_json::from_str::<Snapshot>(&json).unwrap();
+                let mut second = Program::from_snapshot(&snapshot);
+                let mut rest = snapshot.input;
+                let second_outcome = run(&mut second, &mut rest, &mut output, BUDGET - split);
+                assert_eq!(second.ip(), whole.ip(), "case {}: {:?}", case, ints);
+                assert_eq!(second.memory(), whole.memory(), "case {}: {:?}", case, ints);
+                (outcome, second_outcome)
+            }
+            first_outcome => (outcome, first_outcome),
+        };
+
+        assert_eq!(outcome.0, outcome.1, "case {}: {:?}", case, ints);
+        assert_eq!(output, expected, "case {}: {:?}", case, ints);
+    }
+}
+
+#[test]
+fn snapshots_round_trip_through_serde() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    for case in 0..CASES {
+        let (ints, input) = generate(&mut rng);
+        let mut program = Program::new(ints.clone());
+        let mut rest = input.clone();
+        run(&mut program, &mut rest, &mut vec![], BUDGET / 2);
+
+        let mut snapshot = program.snapshot().with_io(rest, vec![7]);
+        snapshot.memory.set(1_000_000, -3);
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let parsed = serde_json::from_str::<Snapshot>(&json).unwrap();
+        assert_eq!(parsed, snapshot, "case {}: {:?}", case, ints);
+    }
+
+    // memory outside the dense region and pending I/O can be left out
+    let snapshot = serde_json::from_str::<Snapshot>(r#"{"ip":4,"rb":0,"memory":[1,0,0,3,99]}"#);
+    let snapshot = snapshot.unwrap();
+    assert_eq!(snapshot.memory.dense(), [1, 0, 0, 3, 99]);
+    assert_eq!(snapshot.memory.sparse().count(), 0);
+    assert!(snapshot.input.is_empty() && snapshot.output.is_empty());
+
+    // values stored sparsely stay sparse, even when they're close enough to the dense region that
+    // writing them would have grown it
+    let mut program = Program::new(vec![1101, 7, 0, 5000, 1101, 1, 0, 4000, 99]);
+    assert_eq!(program.run_no_io(), Ok(StopReason::Halt));
+    let snapshot = program.snapshot();
+    assert_eq!(snapshot.memory.sparse().collect::<Vec<_>>(), [(5000, 7)]);
+    let json = serde_json::to_string(&snapshot).unwrap();
+    let parsed = serde_json::from_str::<Snapshot>(&json).unwrap();
+    assert_eq!(parsed, snapshot);
+    assert_eq!(
+        Program::from_snapshot(&parsed).get_memory(),
+        program.get_memory()
+    );
+
+    let json = r#"{"ip":0,"rb":0,"memory":[99],"sparse":[[10000,7]],"input":[1],"output":[]}"#;
+    let snapshot = serde_json::from_str::<Snapshot>(json).unwrap();
+    assert_eq!(snapshot.memory.get(10000), 7);
+    assert_eq!(serde_json::to_string(&snapshot).unwrap(), json);
+
+    // files hold the same JSON
+    let path = std::env::temp_dir().join(format!("snapshot-{}.json", std::process::id()));
+    snapshot.save(&path).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), json);
+    let loaded = Snapshot::load(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.unwrap(), snapshot);
+    assert!(Snapshot::load(&path).is_err());
+}
@@ -0,0 +1,159 @@
+// Networks of machines: amplifiers in a pipeline or a ring, and machines polling for packets.
+
+use _2019::scheduler::{Network, Pipeline, Ring, Scheduler, Stop, Topology};
+use _2019::{asm, ints_from_str, Int, Program};
+
+/// The signal out of the last amplifier, given each amplifier's phase
+fn signal(ints: &str, phases: &[Int], topology: impl Topology, budget: Option<u64>) -> Int {
+    let programs = phases
+        .iter()
+        .map(|_| Program::new(ints_from_str(ints)))
+        .collect();
+    let mut amplifiers = Scheduler::new(programs, topology);
+    if let Some(budget) = budget {
+        amplifiers = amplifiers.with_budget(budget);
+    }
+    for (amplifier, &phase) in phases.iter().enumerate() {
+        amplifiers.send(amplifier, Some(phase));
+    }
+    amplifiers.send(0, Some(0));
+
+    assert_eq!(amplifiers.run(), Ok(Stop::Halted));
+    assert!(amplifiers.is_halted());
+    amplifiers.last_output(amplifiers.len() - 1).unwrap()
+}
+
+#[test]
+fn amplifiers_in_a_pipeline() {
+    let cases = [
+        ("3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0", [4, 3, 2, 1, 0], 43210),
+        (
+            "3,23,3,24,1002,24,10,24,1002,23,-1,23,101,5,23,23,1,24,23,23,4,23,99,0,0",
+            [0, 1, 2, 3, 4],
+            54321,
+        ),
+        (
+            "3,31,3,32,1002,32,10,32,1001,31,-2,31,1007,31,0,33,1002,33,7,33,1,33,31,31,1,32,31,31,\
+             4,31,99,0,0,0",
+            [1, 0, 4, 3, 2],
+            65210,
+        ),
+    ];
+
+    for (ints, phases, expected) in cases {
+        assert_eq!(signal(ints, &phases, Pipeline, None), expected, "{}", ints);
+        assert_eq!(
+            signal(ints, &phases, Pipeline, Some(3)),
+            expected,
+            "{}",
+            ints
+        );
+    }
+
+    // only the last amplifier's output leaves the network
+    let (ints, phases, _) = cases[0];
+    let programs = phases
+        .iter()
+        .map(|_| Program::new(ints_from_str(ints)))
+        .collect();
+    let mut amplifiers = Scheduler::new(programs, Pipeline);
+    for (amplifier, &phase) in phases.iter().enumerate() {
+        amplifiers.send(amplifier, Some(phase));
+    }
+    amplifiers.send(0, Some(0));
+    assert_eq!(amplifiers.run(), Ok(Stop::Halted));
+    assert_eq!(amplifiers.take_output(), [(4, vec![43210])]);
+    assert!(amplifiers.output().is_empty());
+}
+
+#[test]
+fn amplifiers_in_a_ring() {
+    let cases = [
+        (
+            "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5",
+            [9, 8, 7, 6, 5],
+            139629729,
+        ),
+        (
+            "3,52,1001,52,-5,52,3,53,1,52,56,54,1007,54,5,55,1005,55,26,1001,54,-5,54,1105,1,12,1,\
+             53,54,53,1008,54,0,55,1001,55,1,55,2,53,55,53,4,53,1001,56,-1,56,1005,56,6,99,0,0,0,\
+             0,10",
+            [9, 7, 8, 5, 6],
+            18216,
+        ),
+    ];
+
+    for (ints, phases, expected) in cases {
+        assert_eq!(signal(ints, &phases, Ring, None), expected, "{}", ints);
+        assert_eq!(signal(ints, &phases, Ring, Some(1)), expected, "{}", ints);
+    }
+}
+
+#[test]
+fn waiting_machines_go_idle() {
+    // echoes two values, then halts
+    let ints = ints_from_str("3,9,4,9,3,9,4,9,99,0");
+    let programs = vec![Program::new(ints.clone()), Program::new(ints)];
+    let mut pipeline = Scheduler::new(programs, Pipeline);
+    pipeline.send(0, Some(7));
+
+    // the first value goes all the way through, then both machines wait for the second one
+    assert_eq!(pipeline.run(), Ok(Stop::Idle));
+    assert!(pipeline.is_idle() && !pipeline.is_halted());
+    assert_eq!(pipeline.output(), [(1, vec![7])]);
+
+    pipeline.send(0, Some(8));
+    assert!(!pipeline.is_idle());
+    assert_eq!(pipeline.run(), Ok(Stop::Halted));
+    assert_eq!(pipeline.output(), [(1, vec![7]), (1, vec![8])]);
+    assert_eq!(pipeline.last_output(0), Some(8));
+}
+
+/// A NIC that sends a packet to the next address, then forwards every packet it receives to 255
+const NIC: &str = "
+        in [address]
+        add [address], #1, [next]
+        out [next]
+        out #10
+        out #20
+loop:   in [x]
+        eq [x], #-1, [empty]
+        jt [empty], #loop
+        in [y]
+        out #255
+        out [x]
+        out [y]
+        jt #1, #loop
+address: data 0
+next:   data 0
+x:      data 0
+y:      data 0
+empty:  data 0
+";
+
+#[test]
+fn polling_machines_go_idle() {
+    let ints = asm::assemble(NIC).unwrap().ints;
+    let programs = (0..2).map(|_| Program::new(ints.clone())).collect();
+    let mut network = Scheduler::new(programs, Network::new(3))
+        .with_default_input(-1)
+        .with_budget(1000);
+    network.send(0, Some(0));
+    network.send(1, Some(1));
+
+    // machine 1 gets machine 0's packet, and its own goes to an address that isn't in the network
+    assert_eq!(network.run(), Ok(Stop::Idle));
+    assert!(network.is_idle());
+    assert_eq!(
+        network.take_output(),
+        [(1, vec![2, 10, 20]), (1, vec![255, 10, 20])]
+    );
+
+    // new input wakes the network up again
+    network.send(0, [5, 6]);
+    assert!(!network.is_idle());
+    let stop = network.run_until(|network| !network.output().is_empty());
+    assert_eq!(stop, Ok(Stop::Predicate));
+    assert_eq!(network.take_output(), [(0, vec![255, 5, 6])]);
+    assert_eq!(network.run(), Ok(Stop::Idle));
+}
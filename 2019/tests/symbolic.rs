@@ -0,0 +1,71 @@
+// Symbolic execution, the solver and the brute-force search it falls back on.
+
+use _2019::symbolic::{self, Expr, Machine, Symbol, SymbolicError};
+use _2019::{ints_from_str, Int, StopReason};
+
+#[test]
+fn day_2_is_linear_in_the_noun_and_verb() {
+    let ints = ints_from_str(include_str!("../examples/input/2019-02.txt").trim());
+    let mut machine = Machine::new(&ints).with_symbol(1).with_symbol(2);
+    assert_eq!(machine.run(10_000), Ok(StopReason::Halt));
+
+    let linear = machine.get(0).linear().unwrap();
+    let noun = linear.coefficients[&Symbol::Memory(1)];
+    let verb = linear.coefficients[&Symbol::Memory(2)];
+    assert_eq!(linear.constant + 12 * noun + 2 * verb, 4576384);
+
+    let solution = symbolic::solve_memory(&ints, &[(1, 0..=99), (2, 0..=99)], 0, 19690720);
+    assert_eq!(solution, Some(vec![53, 98]));
+}
+
+#[test]
+fn inputs_build_expressions() {
+    // out (in0 * 3 + in1), then out (in0 < 5)
+    let ints = [
+        3, 100, 3, 101, 1002, 100, 3, 102, 1, 102, 101, 102, 4, 102, 1007, 100, 5, 103, 4, 103, 99,
+    ];
+    let mut machine = Machine::new(&ints);
+    let x = machine.push_symbolic_input();
+    machine.push_symbolic_input();
+    assert_eq!(machine.run(100), Ok(StopReason::Halt));
+
+    let output = machine.output();
+    assert_eq!(output[0].to_string(), "((in0 * 3) + in1)");
+    assert_eq!(output[1], Expr::less_than(x.into(), Expr::from(5)));
+    assert_eq!(output[0].linear().unwrap().to_string(), "3 * in0 + in1");
+
+    // in0 = 4 is the first with a matching in1
+    let ranges = [(Symbol::Input(0), 4..=10), (Symbol::Input(1), 0..=2)];
+    let solution = output[0].linear().unwrap().solve(14, &ranges);
+    assert_eq!(solution, Some(vec![4, 2]));
+    assert_eq!(output[0].linear().unwrap().solve(100, &ranges), None);
+}
+
+#[test]
+fn branching_on_a_symbol_isnt_supported() {
+    let ints = [3, 7, 1005, 7, 6, 99, 99, 0];
+    let mut machine = Machine::new(&ints);
+    machine.push_symbolic_input();
+    assert!(matches!(
+        machine.run(100),
+        Err(SymbolicError::NotConcrete { ip: 2, .. })
+    ));
+
+    // so this falls back to running the program for every combination
+    // [0] = ([13] < [14]) * [14] + [14]
+    let ints = [7, 13, 14, 15, 2, 15, 14, 15, 1, 15, 14, 0, 99, 0, 0, 0];
+    let solution = symbolic::solve_memory(&ints, &[(13, 0..=9), (14, 0..=9)], 0, 18);
+    assert_eq!(solution, Some(vec![0, 9]));
+}
+
+#[test]
+fn search_finds_the_first_combination() {
+    let ranges = [0..=50, -20..=20, 0..=3];
+    let solution = symbolic::search(&ranges, |values: &[Int]| {
+        values[0] * values[1] == -60 && values[2] % 2 == 1
+    });
+    assert_eq!(solution, Some(vec![3, -20, 1]));
+
+    assert_eq!(symbolic::search(&ranges, |_: &[Int]| false), None);
+    assert_eq!(symbolic::search(&[], |_: &[Int]| true), Some(vec![]));
+}
@@ -0,0 +1,124 @@
+// Programs computing with the other word types, and the checked arithmetic mode.
+
+use _2019::{BigInt, Int, IntcodeError, Program, StopReason, Word};
+
+/// Run a program to completion computing with `W`s, returns its output
+fn run<W: Word>(ints: &[Int]) -> Vec<W> {
+    let mut program = Program::with_words(ints.iter().map(|&i| W::from(i)).collect());
+    let mut output = vec![];
+    let reason = program.run(None, &mut output).unwrap();
+    assert_eq!(reason, StopReason::Halt);
+
+    output
+}
+
+fn big(s: &str) -> BigInt {
+    s.parse().unwrap()
+}
+
+#[test]
+fn day_9_runs_with_every_word() {
+    let quine = [
+        109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+    ];
+    let large = [1102, 34915192, 34915192, 7, 4, 7, 99, 0];
+
+    assert_eq!(run::<i128>(&quine), quine.map(i128::from));
+    assert_eq!(run::<i128>(&large), [1219070632396864]);
+    assert_eq!(run::<BigInt>(&quine), quine.map(BigInt::from));
+    assert_eq!(run::<BigInt>(&large), [big("1219070632396864")]);
+}
+
+#[test]
+fn wider_words_dont_overflow() {
+    // 2^62 * 4, written over the instruction and then output
+    let ints = [1102, 1 << 62, 4, 0, 4, 0, 99];
+
+    assert_eq!(run::<i128>(&ints), [1 << 64]);
+    assert_eq!(run::<BigInt>(&ints), [big("18446744073709551616")]);
+    // i64 wraps around unless it's checked
+    assert_eq!(run::<Int>(&ints), [0]);
+}
+
+#[test]
+fn checked_arithmetic_reports_the_ip() {
+    let cases: [(&[Int], usize); 3] = [
+        (&[1102, 1 << 62, 4, 0, 99], 0),
+        (&[1101, 0, 0, 0, 1101, Int::MAX, 1, 0, 99], 4),
+        (&[109, Int::MAX, 109, 1, 99], 2),
+    ];
+
+    for (ints, ip) in cases {
+        let mut program = Program::new(ints.to_vec()).with_checked_arithmetic(true);
+        let error = program.run_no_io().unwrap_err();
+        assert!(
+            matches!(error, IntcodeError::Overflow { ip: i, .. } if i == ip),
+            "{:?}: {}",
+            ints,
+            error
+        );
+    }
+
+    // BigInts never overflow
+    let ints = [1102, 1 << 62, 4, 0, 99].map(BigInt::from).to_vec();
+    let mut program = Program::with_words(ints).with_checked_arithmetic(true);
+    assert_eq!(program.run_no_io().unwrap(), StopReason::Halt);
+    assert_eq!(program.memory().get(0), big("18446744073709551616"));
+}
+
+#[test]
+fn instructions_too_wide_for_an_int_are_invalid() {
+    for instruction in [-(1 << 80), 1 << 80, i128::from(Int::MIN)] {
+        let mut program = Program::<i128>::with_words(vec![instruction]);
+        let error = program.run_no_io().unwrap_err();
+        assert!(matches!(error, IntcodeError::InvalidOpcode { ip: 0, .. }));
+    }
+}
+
+#[test]
+fn big_ints_match_i128() {
+    // xorshift64*, so every run checks the same numbers
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+    let mut next = || {
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        let value = state.wrapping_mul(0x2545_f491_4f6c_dd1d) as Int;
+        // mostly small numbers, so the edge cases around zero come up
+        match value & 3 {
+            0 => value % 1000,
+            _ => value >> (value & 63),
+        }
+    };
+
+    for _ in 0..10_000 {
+        let (a, b) = (next(), next());
+        let (big_a, big_b) = (BigInt::from(a), BigInt::from(b));
+        let (wide_a, wide_b) = (i128::from(a), i128::from(b));
+
+        assert_eq!(big_a.add(&big_b).to_string(), (wide_a + wide_b).to_string());
+        assert_eq!(big_a.mul(&big_b).to_string(), (wide_a * wide_b).to_string());
+        assert_eq!(big_a.cmp(&big_b), a.cmp(&b), "{} {}", a, b);
+        assert_eq!(big_a.to_int(), Some(a));
+        assert_eq!(big(&a.to_string()), big_a);
+        assert_eq!(big_a.add(&big_b).to_int(), a.checked_add(b));
+    }
+}
+
+#[test]
+fn big_ints_grow_past_i128() {
+    let factorial = (1..=40).fold(BigInt::from(1), |product, n| product.mul(&BigInt::from(n)));
+    assert_eq!(
+        factorial.to_string(),
+        "815915283247897734345611269596115894272000000000"
+    );
+    assert_eq!(factorial.to_int(), None);
+    assert_eq!(factorial.clamp_to_int(), Int::MAX);
+
+    let negative = factorial.mul(&BigInt::from(-1));
+    assert_eq!(negative.to_string().parse::<BigInt>().unwrap(), negative);
+    assert!(negative < BigInt::from(Int::MIN));
+    assert!(negative.add(&factorial).is_zero());
+    assert!("12a".parse::<BigInt>().is_err());
+    assert!("-".parse::<BigInt>().is_err());
+}
@@ -0,0 +1,55 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Generate a `#[test]` for every example fixture in `examples/fixtures/{year}-{day}/`.
+/// Each day's solution is included into its own module so the tests can call its `main`.
+fn main() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let fixtures_dir = manifest_dir.join("examples/fixtures");
+    println!("cargo:rerun-if-changed={}", fixtures_dir.display());
+
+    let mut tests = String::new();
+    for day_dir in sorted_entries(&fixtures_dir) {
+        let name = day_dir.file_name().unwrap().to_string_lossy().to_string();
+        let day = match name.split_once('-') {
+            Some((_, day)) if day_dir.is_dir() => day.to_string(),
+            _ => continue,
+        };
+
+        let solution = manifest_dir.join(format!("examples/{}.rs", name));
+        tests.push_str(&format!(
+            "mod day_{} {{\n    include!({:?});\n",
+            day,
+            solution.display().to_string()
+        ));
+        for fixture in sorted_entries(&day_dir) {
+            if fixture.extension().is_none_or(|ext| ext != "txt") {
+                continue;
+            }
+
+            let test_name = fixture
+                .file_stem()
+                .unwrap()
+                .to_string_lossy()
+                .replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+            tests.push_str(&format!(
+                "\n    #[test]\n    fn {}() {{\n        aoc_lib::fixtures::check({:?}, main);\n    }}\n",
+                test_name,
+                fixture.display().to_string()
+            ));
+        }
+        tests.push_str("}\n\n");
+    }
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    fs::write(out_dir.join("fixtures.rs"), tests).unwrap();
+}
+
+fn sorted_entries(dir: &Path) -> Vec<PathBuf> {
+    let mut entries = fs::read_dir(dir)
+        .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+        .unwrap_or_else(|_| vec![]);
+    entries.sort();
+    entries
+}
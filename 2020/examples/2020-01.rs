@@ -55,7 +55,6 @@ fn main() {
     let input = include_str!("./input/2020-01.txt");
     let numbers = input
         .split_ascii_whitespace()
-        .into_iter()
         .map(|number| number.parse::<usize>().unwrap())
         .collect::<Vec<usize>>();
 
@@ -161,11 +161,7 @@ impl Passport {
                 let hcl = hcl.chars().collect::<Vec<char>>();
                 if hcl[0] != '#'
                     || hcl.len() != 7
-                    || hcl.iter().all(|c| match c {
-                        '0'..='9' => true,
-                        'a'..='f' => true,
-                        _ => false,
-                    })
+                    || hcl.iter().all(|c| matches!(c, '0'..='9' | 'a'..='f'))
                 {
                     return false;
                 }
@@ -191,12 +187,9 @@ impl Passport {
             None => return false,
         }
 
-        match self.ecl.as_ref().map(|s| s.as_str()) {
-            Some(s) => match s {
-                "amb" | "blu" | "brn" | "gry" | "grn" | "hzl" | "oth" => true,
-                _ => return false,
-            },
-            None => return false,
+        match self.ecl.as_deref() {
+            Some("amb" | "blu" | "brn" | "gry" | "grn" | "hzl" | "oth") => true,
+            _ => return false,
         };
 
         match self.pid.as_ref() {
@@ -254,7 +247,7 @@ fn main() {
     let mut passports = vec![];
     let mut passport_str = String::new();
     for line in input.lines() {
-        if line.len() == 0 {
+        if line.is_empty() {
             passports.push(passport_str.clone());
             passport_str = String::new();
         }
@@ -117,7 +117,7 @@ fn main() {
             .chars()
             .filter(|ch| ch.is_ascii_alphabetic())
             .collect::<HashSet<char>>();
-        total_1 += set.iter().count();
+        total_1 += set.len();
 
         let person_sets = group
             .lines()
@@ -104,7 +104,7 @@ fn n_bags_required_inside(
 ) -> usize {
     child_rule_map.get(color).map_or(0, |children| {
         children.iter().fold(0, |result, (color, count)| {
-            result + count + count * n_bags_required_inside(color, &child_rule_map)
+            result + count + count * n_bags_required_inside(color, child_rule_map)
         })
     })
 }
@@ -123,8 +123,8 @@ fn main() {
             .or_insert(vec![]);
         if !bag_contents.contains("no other bags") {
             let re = Regex::new(r"(\d+)(.+?)bags?").unwrap();
-            for cap in re.captures_iter(&bag_contents) {
-                let child_name = (&cap[2]).trim().to_string();
+            for cap in re.captures_iter(bag_contents) {
+                let child_name = cap[2].trim().to_string();
                 let child_count = cap[1].parse::<usize>().unwrap();
                 child_rules.push((child_name.clone(), child_count));
 
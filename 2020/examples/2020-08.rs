@@ -141,7 +141,7 @@ fn test(lines: Vec<&str>, part_1: bool) -> isize {
         let (instruction, number) = aoc_lib::utils::string_split2(" ", line);
         let number = number.parse::<isize>().unwrap();
 
-        match &instruction[..] {
+        match instruction {
             "acc" => {
                 mem += number;
                 ip += 1;
@@ -149,17 +149,17 @@ fn test(lines: Vec<&str>, part_1: bool) -> isize {
             "jmp" => {
                 ip = ((ip as isize) + number) as usize;
             }
-            "nop" | _ => {
+            _ => {
                 ip += 1;
             }
         }
     }
 
-    return mem;
+    mem
 }
 
 fn main() {
-    let input = include_str!("./input/2020-08.txt").trim();
+    let input = aoc_lib::input!("./input/2020-08.txt").trim();
     let lines = input.lines().collect::<Vec<&str>>();
 
     for (i, line) in lines.iter().enumerate() {
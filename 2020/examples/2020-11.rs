@@ -257,15 +257,15 @@ fn step1(lines: Vec<Vec<char>>) -> Vec<Vec<char>> {
 
                         let k_start = if i == 0 { 0 } else { i - 1 };
                         let k_end = if i == lines.len() - 1 { i } else { i + 1 };
-                        for k in k_start..=k_end {
+                        for (k, line) in lines.iter().enumerate().take(k_end + 1).skip(k_start) {
                             let l_start = if j == 0 { 0 } else { j - 1 };
                             let l_end = if j == chars.len() - 1 { j } else { j + 1 };
-                            for l in l_start..=l_end {
+                            for (l, c) in line.iter().enumerate().take(l_end + 1).skip(l_start) {
                                 if k == i && l == j {
                                     continue;
                                 }
 
-                                if lines[k][l] == '#' {
+                                if *c == '#' {
                                     n += 1;
                                 }
                             }
@@ -138,7 +138,7 @@ fn part1(actions: Vec<(char, isize)>) -> Point {
             'L' | 'R' => {
                 let d = if direction == 'L' { -1 } else { 1 };
                 match count {
-                    90 => facing = (facing as isize + (1 * d)) as usize % DIRECTIONS.len(),
+                    90 => facing = (facing as isize + d) as usize % DIRECTIONS.len(),
                     180 => facing = (facing as isize + (2 * d)) as usize % DIRECTIONS.len(),
                     270 => facing = (facing as isize + (3 * d)) as usize % DIRECTIONS.len(),
                     360 => {}
@@ -106,7 +106,7 @@ fn solve_recursive(possible: &Vec<Vec<usize>>, i: usize, path: Vec<usize>) -> Ve
     } else {
         let mut paths = vec![];
         for j in &possible[i] {
-            if path.contains(&j) {
+            if path.contains(j) {
                 continue;
             }
 
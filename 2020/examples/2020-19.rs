@@ -211,9 +211,9 @@ fn rule_to_regex(
         .join("|");
 
     if list.len() == 2 {
-        s.push_str("(");
+        s.push('(');
         s.push_str(rule_str);
-        s.push_str(")");
+        s.push(')');
     } else {
         s.push_str(rule_str);
     }
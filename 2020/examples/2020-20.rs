@@ -374,7 +374,7 @@ fn sides_can_match<S: AsRef<str>>(a: S, b: S) -> bool {
     a == b || a.chars().rev().collect::<String>() == b
 }
 
-fn rotate(input: &Vec<Vec<char>>) -> Vec<Vec<char>> {
+fn rotate(input: &[Vec<char>]) -> Vec<Vec<char>> {
     let len = input.len();
     let mut output = vec![vec![char::default(); len]; len];
     for y in 0..len {
@@ -386,7 +386,7 @@ fn rotate(input: &Vec<Vec<char>>) -> Vec<Vec<char>> {
     output
 }
 
-fn flip_v(input: &Vec<Vec<char>>) -> Vec<Vec<char>> {
+fn flip_v(input: &[Vec<char>]) -> Vec<Vec<char>> {
     input.iter().rev().cloned().collect()
 }
 
@@ -463,7 +463,7 @@ fn main() -> Result<()> {
                     let (x, y) = ((x as isize) + dx, (y as isize) + dy);
                     let (x, y) = (x as usize, y as usize);
                     if let Some(Some(Some(id))) = pieces.get(y).map(|row| row.get(x)) {
-                        available &= adjacency_list.get(&id).unwrap();
+                        available &= adjacency_list.get(id).unwrap();
                     }
                 }
 
@@ -513,11 +513,11 @@ fn main() -> Result<()> {
     // remove borders and build final picture
     let picture = {
         let mut data: Vec<Vec<char>> = vec![];
-        for y in 0..n_tiles {
+        for row_pieces in pieces.iter().take(n_tiles) {
             for i in 1..9 {
                 let mut line = vec![];
-                for x in 0..n_tiles {
-                    let tile = tiles.get(&pieces[y][x].unwrap()).unwrap();
+                for piece in row_pieces.iter().take(n_tiles) {
+                    let tile = tiles.get(&piece.unwrap()).unwrap();
                     let row = &tile.data[i];
                     line.extend(&row[1..9].to_vec());
                 }
@@ -79,6 +79,7 @@ fn main() -> Result<()> {
     let mut ingredients_to_allergens = HashMap::new();
     for line in input.lines() {
         let captures = re_ingredients.captures(line).unwrap();
+        #[allow(clippy::trim_split_whitespace)]
         let ingredients = c!(captures, 1).split_whitespace().collect::<Vec<_>>();
         let captures = re_allergens.captures(c!(captures, 2)).unwrap();
         let allergens = c!(captures, 1).split(", ").collect::<Vec<_>>();
@@ -101,7 +102,7 @@ fn main() -> Result<()> {
         let possible = ingredients_to_allergens.get_mut(ing).unwrap();
         for al in possible.clone() {
             for (allergens, ingredients) in &allergens_to_ingredients {
-                if allergens.contains(&al) && !ingredients.contains(&ing) {
+                if allergens.contains(&al) && !ingredients.contains(ing) {
                     possible.remove(&al);
                 }
             }
@@ -130,8 +131,8 @@ fn main() -> Result<()> {
         .any(|(_, als)| als.len() > 1)
     {
         let singles = ingredients_to_allergens
-            .iter()
-            .filter_map(|(_, als)| {
+            .values()
+            .filter_map(|als| {
                 if als.len() == 1 {
                     Some(als.iter().next().unwrap())
                 } else {
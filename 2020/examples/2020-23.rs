@@ -114,7 +114,7 @@
 
 use anyhow::Result;
 
-fn play(cups: &Vec<usize>, count: usize) -> Vec<usize> {
+fn play(cups: &[usize], count: usize) -> Vec<usize> {
     // build up a map (using a vec) of cup_number->cup_to_the_right
     let mut right_of = vec![0; cups.len() + 1];
     for (i, n) in cups.iter().enumerate() {
@@ -158,12 +158,12 @@ fn play(cups: &Vec<usize>, count: usize) -> Vec<usize> {
 }
 
 fn main() -> Result<()> {
-    let input = include_str!("./input/2020-23.txt").trim();
+    let input = aoc_lib::input!("./input/2020-23.txt").trim();
     let mut label = input
         .chars()
         .map(|c| c.to_digit(10).unwrap() as usize)
         .collect::<Vec<_>>();
-    aoc_lib::set_part_1!(play(&label, 100)
+    aoc_lib::set_part_1!(play(&label, aoc_lib::fixtures::param("moves", 100))
         .iter()
         .cycle()
         .skip_while(|n| **n != 1)
@@ -100,23 +100,27 @@ use mod_exp::mod_exp;
 
 fn transform(subject: usize, loop_size: usize) -> usize {
     mod_exp(subject, loop_size, 20201227)
+}
 
-    // NOTE: fast exponentiation algorithm
-    // if loop_size == 0 {
-    //     1
-    // } else if loop_size % 2 == 0 {
-    //     transform((subject * subject) % 20201227, loop_size / 2)
-    // } else {
-    //     (subject * transform(subject, loop_size - 1)) % 20201227
-    // }
+// NOTE: fast exponentiation algorithm
+fn transform_fast(subject: usize, loop_size: usize) -> usize {
+    if loop_size == 0 {
+        1
+    } else if loop_size.is_multiple_of(2) {
+        transform_fast((subject * subject) % 20201227, loop_size / 2)
+    } else {
+        (subject * transform_fast(subject, loop_size - 1)) % 20201227
+    }
+}
 
-    // NOTE: naive algorithm
-    // let mut result = 1;
-    // for _ in 0..loop_size {
-    //     result *= subject;
-    //     result %= 20201227;
-    // }
-    // result
+// NOTE: naive algorithm
+fn transform_naive(subject: usize, loop_size: usize) -> usize {
+    let mut result = 1;
+    for _ in 0..loop_size {
+        result *= subject;
+        result %= 20201227;
+    }
+    result
 }
 
 fn find_loop_size(subject: usize, pubkey: usize) -> usize {
@@ -129,7 +133,7 @@ fn find_loop_size(subject: usize, pubkey: usize) -> usize {
 }
 
 fn main() -> Result<()> {
-    let input = include_str!("./input/2020-25.txt").trim();
+    let input = aoc_lib::input!("./input/2020-25.txt").trim();
     let numbers = input
         .lines()
         .map(|line| line.parse::<usize>().unwrap())
@@ -137,7 +141,11 @@ fn main() -> Result<()> {
 
     let (card_pubkey, door_pubkey) = (numbers[0], numbers[1]);
     let card_loop_size = find_loop_size(7, card_pubkey);
-    let encryption_key = transform(door_pubkey, card_loop_size);
+    let encryption_key = aoc_lib::variants! {
+        "mod_exp" => transform(door_pubkey, card_loop_size),
+        "fast" => transform_fast(door_pubkey, card_loop_size),
+        "naive" => transform_naive(door_pubkey, card_loop_size),
+    };
 
     aoc_lib::set_part_1!(encryption_key);
     aoc_lib::set_part_2!("there is no part 2!");
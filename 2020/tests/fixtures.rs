@@ -0,0 +1,2 @@
+// One test per example fixture, see `build.rs`.
+include!(concat!(env!("OUT_DIR"), "/fixtures.rs"));
@@ -130,9 +130,9 @@ fn main() -> Result<()> {
     aoc_lib::set_part_1!(n_increased);
 
     let mut n_increased = 0;
-    let mut last = depths.iter().take(3).fold(0, |r, x| r + x);
+    let mut last = depths.iter().take(3).sum::<u64>();
     for window in depths.windows(3).skip(1) {
-        let total = window.iter().fold(0, |r, x| r + x);
+        let total = window.iter().sum::<u64>();
         if total > last {
             n_increased += 1;
         }
@@ -96,8 +96,8 @@ struct Point(i64, i64);
 
 impl Point {
     pub fn line_between(&self, other: &Point) -> Vec<Point> {
-        let mut points = vec![self.clone()];
-        let mut cur = self.clone();
+        let mut points = vec![*self];
+        let mut cur = *self;
         loop {
             if cur.0 != other.0 {
                 cur.0 = if cur.0 > other.0 {
@@ -114,7 +114,7 @@ impl Point {
                 };
             }
 
-            points.push(cur.clone());
+            points.push(cur);
 
             if cur.0 == other.0 && cur.1 == other.1 {
                 break;
@@ -115,7 +115,7 @@ fn item_to_priority(item: char) -> usize {
 }
 
 fn str_to_priorities(s: &str) -> HashSet<usize> {
-    return s.chars().map(item_to_priority).collect();
+    s.chars().map(item_to_priority).collect()
 }
 
 fn main() -> Result<()> {
@@ -152,7 +152,7 @@ fn main() -> Result<()> {
                 });
 
             assert!(set.len() == 1);
-            set.iter().next().unwrap().clone()
+            *set.iter().next().unwrap()
         })
         .sum::<usize>();
 
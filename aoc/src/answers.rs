@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+
+/// A recorded (known correct) answer for one part of a puzzle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Answer {
+    pub answer: String,
+    /// When the answer was accepted (seconds since the unix epoch), if it was submitted by `aoc`
+    pub solved_at: Option<u64>,
+}
+
+/// The recorded answers for a year, stored in `{year}/answers.txt` with one line per part:
+///
+/// ```text
+/// <day> <part> <solved_at or -> <answer>
+/// ```
+#[derive(Debug, Clone)]
+pub struct Answers {
+    year: usize,
+    entries: BTreeMap<(usize, usize), Answer>,
+}
+
+impl Answers {
+    pub fn path(year: usize) -> PathBuf {
+        PathBuf::from(format!("{}/answers.txt", year))
+    }
+
+    pub fn load(year: usize) -> Result<Answers> {
+        let mut answers = Answers {
+            year,
+            entries: BTreeMap::new(),
+        };
+
+        let contents = match fs::read_to_string(Answers::path(year)) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(answers),
+            Err(e) => return Err(e.into()),
+        };
+
+        for line in contents.lines().filter(|l| !l.trim().is_empty()) {
+            let mut parts = line.splitn(4, ' ');
+            let mut next = || {
+                parts
+                    .next()
+                    .ok_or_else(|| anyhow!("invalid answer line: '{}'", line))
+            };
+
+            let day = next()?.parse::<usize>()?;
+            let part = next()?.parse::<usize>()?;
+            let solved_at = match next()? {
+                "-" => None,
+                timestamp => Some(timestamp.parse::<u64>()?),
+            };
+            let answer = next()?.to_string();
+
+            answers
+                .entries
+                .insert((day, part), Answer { answer, solved_at });
+        }
+
+        Ok(answers)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let mut contents = String::new();
+        for ((day, part), entry) in &self.entries {
+            let solved_at = entry
+                .solved_at
+                .map(|t| t.to_string())
+                .unwrap_or_else(|| String::from("-"));
+            contents.push_str(&format!(
+                "{:02} {} {} {}\n",
+                day, part, solved_at, entry.answer
+            ));
+        }
+
+        Ok(fs::write(Answers::path(self.year), contents)?)
+    }
+
+    pub fn get(&self, day: usize, part: usize) -> Option<&Answer> {
+        self.entries.get(&(day, part))
+    }
+
+    pub fn insert(&mut self, day: usize, part: usize, answer: Answer) {
+        self.entries.insert((day, part), answer);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Record an answer that was just accepted by the Advent of Code website
+pub fn record_submission(year: usize, day: usize, part: usize, answer: &str) -> Result<()> {
+    let solved_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+
+    let mut answers = Answers::load(year)?;
+    answers.insert(
+        day,
+        part,
+        Answer {
+            answer: answer.trim().to_string(),
+            solved_at: Some(solved_at),
+        },
+    );
+    answers.save()
+}
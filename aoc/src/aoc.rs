@@ -11,7 +11,7 @@ use crate::credentials;
 
 const BASE_URL: &str = "https://adventofcode.com";
 
-#[derive(Copy, Clone, strum_macros::EnumString, strum_macros::ToString)]
+#[derive(Copy, Clone, strum_macros::EnumString, strum_macros::Display)]
 pub enum AocPart {
     #[strum(serialize = "part_1", to_string = "1")]
     One,
@@ -19,6 +19,15 @@ pub enum AocPart {
     Two,
 }
 
+impl AocPart {
+    pub fn number(self) -> usize {
+        match self {
+            AocPart::One => 1,
+            AocPart::Two => 2,
+        }
+    }
+}
+
 pub async fn get_input(client: &Client, year: usize, day: usize) -> Result<String> {
     Ok(client
         .get(&format!(
@@ -50,7 +59,7 @@ pub async fn get_description(client: &Client, year: usize, day: usize) -> Result
     let mut description = format!("// See: {}\n", url);
     for element in document.select(&selector) {
         // Line length = 100 - 3 (comment length)
-        let text = html2text::from_read(&element.html().as_bytes()[..], 100 - 3);
+        let text = html2text::from_read(element.html().as_bytes(), 100 - 3);
         for line in text.lines().map(|l| l.trim()) {
             if !line.is_empty() {
                 description.push_str(&format!("// {}\n", line));
@@ -93,7 +102,7 @@ pub async fn submit_answer(
 
             println!("Uh oh! Either your answer was incorrect or there was an issue submitting.");
             if let Some(main) = document.select(&selector).next() {
-                let text = html2text::from_read(&main.html().as_bytes()[..], 80);
+                let text = html2text::from_read(main.html().as_bytes(), 80);
                 println!("---\n{}\n---", text.trim());
             }
 
@@ -141,7 +150,7 @@ pub async fn create_or_update_challenge(client: &Client, year: usize, day: usize
         .create(true)
         .read(true)
         .write(true)
-        .open(&format!(
+        .open(format!(
             "{year}/examples/{year}-{day:02}.rs",
             year = year,
             day = day
@@ -152,7 +161,7 @@ pub async fn create_or_update_challenge(client: &Client, year: usize, day: usize
             let mut contents = String::new();
             f.read_to_string(&mut contents)?;
 
-            let mut updated = String::from(description);
+            let mut updated = description;
             contents
                 .lines()
                 .skip_while(|line| line.starts_with("//"))
@@ -168,7 +177,7 @@ pub async fn create_or_update_challenge(client: &Client, year: usize, day: usize
 }
 
 pub fn is_part_1_complete(year: usize, day: usize) -> Result<bool> {
-    Ok(fs::read_to_string(&format!(
+    Ok(fs::read_to_string(format!(
         "{year}/examples/{year}-{day:02}.rs",
         year = year,
         day = day
@@ -176,6 +185,43 @@ pub fn is_part_1_complete(year: usize, day: usize) -> Result<bool> {
     .contains("--- Part Two ---"))
 }
 
+/// All the days of a year that have a solution
+pub fn list_days(year: usize) -> Result<Vec<usize>> {
+    let prefix = format!("{}-", year);
+    let mut days = fs::read_dir(format!("{}/examples", year))?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.strip_prefix(&prefix)?
+                .strip_suffix(".rs")?
+                .parse()
+                .ok()
+        })
+        .collect::<Vec<usize>>();
+    days.sort_unstable();
+
+    Ok(days)
+}
+
+/// The puzzle's title, taken from the description at the top of its source file
+pub fn get_title(year: usize, day: usize) -> Result<Option<String>> {
+    Ok(fs::read_to_string(format!(
+        "{year}/examples/{year}-{day:02}.rs",
+        year = year,
+        day = day
+    ))?
+    .lines()
+    .take_while(|line| line.starts_with("//"))
+    .find_map(|line| {
+        let title = line
+            .trim_start_matches('/')
+            .trim()
+            .strip_prefix("## --- Day ")?;
+        let (_, title) = title.split_once(": ")?;
+        Some(title.trim_end_matches(" ---").to_string())
+    }))
+}
+
 fn new_source_file(description: &str, year: usize, day: usize) -> String {
     format!(
         r#"{description}
@@ -183,7 +229,7 @@ fn new_source_file(description: &str, year: usize, day: usize) -> String {
 use anyhow::Result;
 
 fn main() -> Result<()> {{
-    let input = include_str!("./input/{year}-{day:02}.txt").trim();
+    let input = aoc_lib::input!("./input/{year}-{day:02}.txt").trim();
 
     aoc_lib::set_part_1!(0);
     // aoc_lib::set_part_2!(0);
@@ -1,8 +1,42 @@
-use clap::{crate_authors, Parser};
+use std::path::PathBuf;
+
+use clap::{crate_authors, AppSettings, Parser, Subcommand};
+
+use crate::report::ReportFormat;
 
 #[derive(Parser)]
 #[clap(author = crate_authors!())]
+#[clap(setting = AppSettings::SubcommandsNegateReqs)]
+#[clap(setting = AppSettings::ArgsNegateSubcommands)]
 pub struct Args {
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+
+    #[clap(required = true)]
+    pub year: Option<usize>,
+    #[clap(required = true)]
+    pub day: Option<usize>,
+
+    #[clap(short = 'r', long = "release")]
+    pub release: bool,
+
+    /// Report allocations and peak heap usage for each part
+    #[clap(short = 'a', long = "alloc")]
+    pub alloc: bool,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Check a solution against the examples in its fixtures directory
+    Test(TestArgs),
+    /// Run every solution of a year in parallel and summarise the answers and timings
+    Run(RunArgs),
+    /// Summarise stars, solve times and run times from the recorded answers and timings
+    Report(ReportArgs),
+}
+
+#[derive(Parser)]
+pub struct TestArgs {
     pub year: usize,
     pub day: usize,
 
@@ -10,8 +44,53 @@ pub struct Args {
     pub release: bool,
 }
 
+#[derive(Parser)]
+pub struct RunArgs {
+    #[clap(long = "year", required_unless_present = "all")]
+    pub year: Option<usize>,
+
+    /// Run the solutions of every year
+    #[clap(long = "all")]
+    pub all: bool,
+
+    #[clap(short = 'r', long = "release")]
+    pub release: bool,
+
+    /// Seconds after which a solution is stopped
+    #[clap(long = "timeout", default_value = "60")]
+    pub timeout: u64,
+
+    /// Record the run times, and the answers of parts that don't have a recorded answer yet
+    #[clap(long = "record")]
+    pub record: bool,
+}
+
+#[derive(Parser)]
+pub struct ReportArgs {
+    /// Only report on a single year (defaults to all years)
+    #[clap(long = "year")]
+    pub year: Option<usize>,
+
+    /// One of: markdown, html, csv
+    #[clap(short = 'f', long = "format", default_value = "markdown")]
+    pub format: ReportFormat,
+
+    /// Write the report to a file instead of stdout
+    #[clap(short = 'o', long = "output")]
+    pub output: Option<PathBuf>,
+
+    /// Rewrite the report section of README.md (always markdown)
+    #[clap(long = "readme", conflicts_with_all = &["format", "output"])]
+    pub readme: bool,
+}
+
 impl Args {
     pub fn parse() -> Args {
         <Args as Parser>::parse()
     }
+
+    /// The year and day of the watch loop (both are required unless a subcommand is given)
+    pub fn year_day(&self) -> (usize, usize) {
+        (self.year.unwrap(), self.day.unwrap())
+    }
 }
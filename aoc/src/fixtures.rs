@@ -0,0 +1,224 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{Debug, Display};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::args::TestArgs;
+
+/// Separates a fixture's header (expected answers and parameters) from the example input
+const SEPARATOR: &str = "---";
+
+thread_local! {
+    static ACTIVE: RefCell<Option<Fixture>> = const { RefCell::new(None) };
+}
+
+/// An example taken from a puzzle description, stored as
+/// `{year}/examples/fixtures/{year}-{day}/{name}.txt`:
+///
+/// ```text
+/// part_1: 5
+/// part_2: 8
+/// moves: 10
+/// ---
+/// <example input>
+/// ```
+///
+/// `part_1` and `part_2` are the expected answers (either may be left out), every other key is a
+/// parameter that the solution can read with `fixtures::param`.
+#[derive(Debug, Clone)]
+pub struct Fixture {
+    pub name: String,
+    pub input: String,
+    pub params: HashMap<String, String>,
+    pub part_1: Option<String>,
+    pub part_2: Option<String>,
+    answers: HashMap<String, String>,
+}
+
+impl Fixture {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Fixture> {
+        let path = path.as_ref();
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .ok_or_else(|| anyhow!("invalid fixture path: {}", path.display()))?;
+
+        Fixture::parse(name, &fs::read_to_string(path)?)
+    }
+
+    pub fn parse(name: impl Into<String>, contents: &str) -> Result<Fixture> {
+        let name = name.into();
+        let separator = format!("{}\n", SEPARATOR);
+        let (header, input) = match contents.strip_prefix(&separator) {
+            Some(input) => ("", input),
+            None => match contents.split_once(&format!("\n{}", separator)) {
+                Some(parts) => parts,
+                None => bail!("fixture {} is missing a '{}' line", name, SEPARATOR),
+            },
+        };
+
+        let mut params = HashMap::new();
+        for line in header.lines().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+            match line.split_once(':') {
+                Some((key, value)) => {
+                    params.insert(key.trim().to_string(), value.trim().to_string());
+                }
+                None => bail!("fixture {} has an invalid header line: '{}'", name, line),
+            }
+        }
+
+        Ok(Fixture {
+            name,
+            input: input.to_string(),
+            part_1: params.remove("part_1"),
+            part_2: params.remove("part_2"),
+            params,
+            answers: HashMap::new(),
+        })
+    }
+}
+
+/// Return the input of the active fixture, or `default` (the real puzzle input) otherwise
+pub fn input(default: &'static str) -> &'static str {
+    ACTIVE.with(|active| match active.borrow().as_ref() {
+        // fixtures are small and only live as long as a test, so leaking them is fine
+        Some(fixture) => Box::leak(fixture.input.clone().into_boxed_str()),
+        None => default,
+    })
+}
+
+/// Read an example-only parameter from the active fixture, or return `default` otherwise
+pub fn param<T>(name: &str, default: T) -> T
+where
+    T: FromStr,
+    T::Err: Debug,
+{
+    ACTIVE.with(|active| {
+        match active
+            .borrow()
+            .as_ref()
+            .and_then(|fixture| fixture.params.get(name))
+        {
+            Some(value) => value
+                .parse()
+                .unwrap_or_else(|e| panic!("invalid fixture parameter {}: {:?}", name, e)),
+            None => default,
+        }
+    })
+}
+
+/// Record an answer against the active fixture, returns `false` if there isn't one
+pub fn record(part: &str, value: &dyn Display) -> bool {
+    ACTIVE.with(|active| match active.borrow_mut().as_mut() {
+        Some(fixture) => {
+            fixture.answers.insert(part.to_string(), value.to_string());
+            true
+        }
+        None => false,
+    })
+}
+
+/// Anything a solution's `main` may return
+pub trait MainResult {
+    fn into_result(self) -> Result<(), String>;
+}
+
+impl MainResult for () {
+    fn into_result(self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl<E: Debug> MainResult for Result<(), E> {
+    fn into_result(self) -> Result<(), String> {
+        self.map_err(|e| format!("{:?}", e))
+    }
+}
+
+/// Run a solution's `main` against a fixture and assert that it produced the expected answers
+pub fn check<R: MainResult>(path: impl AsRef<Path>, main: fn() -> R) {
+    let fixture = Fixture::from_file(path.as_ref())
+        .unwrap_or_else(|e| panic!("failed to load {}: {}", path.as_ref().display(), e));
+    let (part_1, part_2) = (fixture.part_1.clone(), fixture.part_2.clone());
+
+    ACTIVE.with(|active| *active.borrow_mut() = Some(fixture));
+    let result = main().into_result();
+    let fixture = ACTIVE.with(|active| active.borrow_mut().take()).unwrap();
+
+    if let Err(e) = result {
+        panic!("{} failed: {}", fixture.name, e);
+    }
+
+    for (part, expected) in [("part_1", part_1), ("part_2", part_2)] {
+        if let Some(expected) = expected {
+            assert_eq!(
+                fixture.answers.get(part),
+                Some(&expected),
+                "{} gave the wrong answer for {}",
+                fixture.name,
+                part
+            );
+        }
+    }
+}
+
+pub fn fixtures_dir(year: usize, day: usize) -> PathBuf {
+    PathBuf::from(format!(
+        "{year}/examples/fixtures/{year}-{day:02}",
+        year = year,
+        day = day
+    ))
+}
+
+/// The test target that a year's build script fills with a test per fixture. Only 2020 has one so
+/// far, see its `build.rs`.
+pub fn test_target(year: usize) -> PathBuf {
+    PathBuf::from(format!("{}/tests/fixtures.rs", year))
+}
+
+/// `aoc test`: run the generated fixture tests for a single day
+pub fn run(args: &TestArgs) -> Result<()> {
+    if !test_target(args.year).is_file() {
+        bail!(
+            "no fixtures for {} (it has no {})",
+            args.year,
+            test_target(args.year).display()
+        );
+    }
+
+    let dir = fixtures_dir(args.year, args.day);
+    if !dir.is_dir() {
+        bail!(
+            "no fixtures found for {}-{:02} in {}",
+            args.year,
+            args.day,
+            dir.display()
+        );
+    }
+
+    let package = format!("_{}", args.year);
+    let filter = format!("day_{:02}::", args.day);
+    let mut cargo_args = vec!["test", "-p", &package, "--test", "fixtures"];
+    if args.release {
+        cargo_args.push("--release");
+    }
+    cargo_args.extend(["--", &filter]);
+
+    let status = Command::new("cargo")
+        .args(&cargo_args)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(anyhow!("fixtures failed for {}-{:02}", args.year, args.day))
+    }
+}
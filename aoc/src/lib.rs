@@ -1,7 +1,14 @@
+pub mod alloc;
+pub mod answers;
 pub mod aoc;
 pub mod args;
 pub mod credentials;
+pub mod fixtures;
+pub mod report;
+pub mod run;
+pub mod timings;
 pub mod utils;
+pub mod variants;
 
 use paste::paste;
 
@@ -15,17 +22,30 @@ macro_rules! define_aoc_macro {
                     use std::io::Write;
 
                     let value = $result;
+                    let alloc_stats = aoc_lib::alloc::take();
                     println!("{}: {}", stringify!($ident), value);
-                    OpenOptions::new()
-                        .create(true)
-                        .truncate(true)
-                        .write(true)
-                        .open(concat!("/tmp/aoc-{}", stringify!($ident)))
-                        .unwrap()
-                        .write_all(format!("{}", value).as_bytes())
-                        .unwrap();
+                    if let Some(alloc_stats) = alloc_stats {
+                        println!("{} memory: {}", stringify!($ident), alloc_stats);
+                    }
+
+                    // answers for example fixtures (or from `aoc run`) shouldn't replace the real answer
+                    if !aoc_lib::fixtures::record(stringify!($ident), &value)
+                        && std::env::var_os("AOC_NO_SAVE").is_none()
+                    {
+                        OpenOptions::new()
+                            .create(true)
+                            .truncate(true)
+                            .write(true)
+                            .open(concat!("/tmp/aoc-{}", stringify!($ident)))
+                            .unwrap()
+                            .write_all(format!("{}", value).as_bytes())
+                            .unwrap();
+                    }
 
-                        value
+                    // don't count the bookkeeping above towards the next part
+                    aoc_lib::alloc::reset();
+
+                    value
                 }};
             }
 
@@ -56,7 +76,13 @@ macro_rules! define_aoc_macro {
                     match [<get_ $ident>]!() {
                         Ok(answer) => {
                             println!("Submitting {} answer: '{}'...", stringify!($ident), &answer);
-                            aoc::submit_answer($client, $year, $day, AocPart::from_str(stringify!($ident))?, answer.as_str()).await?
+                            let part = AocPart::from_str(stringify!($ident))?;
+                            let correct = aoc::submit_answer($client, $year, $day, part, answer.as_str()).await?;
+                            if correct {
+                                aoc_lib::answers::record_submission($year, $day, part.number(), &answer)?;
+                            }
+
+                            correct
                         },
                         Err(e) => {
                             if e.kind() == std::io::ErrorKind::NotFound {
@@ -74,5 +100,14 @@ macro_rules! define_aoc_macro {
     };
 }
 
+/// Include the puzzle input, unless the solution is being run against an example fixture
+/// (see `fixtures::check`), in which case that fixture's input is returned instead.
+#[macro_export]
+macro_rules! input {
+    ($path:expr) => {
+        aoc_lib::fixtures::input(include_str!($path))
+    };
+}
+
 define_aoc_macro!(part_1);
 define_aoc_macro!(part_2);
@@ -5,7 +5,7 @@ use std::thread;
 use std::time::Duration;
 
 use anyhow::Result;
-use aoc_lib::args::Args;
+use aoc_lib::args::{Args, Command as AocCommand};
 use aoc_lib::utils::prompt_from_stdin;
 use reqwest::Client;
 
@@ -16,13 +16,15 @@ enum Action {
 }
 
 async fn run_loop(client: &Client, args: &Args, running: &Arc<AtomicBool>) -> Result<Action> {
+    let (year, day) = args.year_day();
+
     // create new challenge if it doesn't exist
     println!(
         "Loading challenge {year}-{day:02}...",
-        year = args.year,
-        day = args.day
+        year = year,
+        day = day
     );
-    aoc_lib::aoc::create_or_update_challenge(&client, args.year, args.day).await?;
+    aoc_lib::aoc::create_or_update_challenge(client, year, day).await?;
 
     // clean up old answers
     aoc_lib::remove_part_1!();
@@ -43,14 +45,19 @@ async fn run_loop(client: &Client, args: &Args, running: &Arc<AtomicBool>) -> Re
     // start a watch/run loop
     println!("Starting watch loop...");
     let mut child = Command::new("cargo")
-        .args(&[
+        .args([
             "watch",
             "-x",
             &format!(
-                "run {release} --example {year}-{day:02}",
+                "run {release} {features} --example {year}-{day:02}",
                 release = if args.release { "--release" } else { "" },
-                year = args.year,
-                day = args.day
+                features = if args.alloc {
+                    "--features alloc-stats"
+                } else {
+                    ""
+                },
+                year = year,
+                day = day
             ),
         ])
         .stdin(Stdio::inherit())
@@ -70,14 +77,14 @@ async fn run_loop(client: &Client, args: &Args, running: &Arc<AtomicBool>) -> Re
     let answer = prompt_from_stdin(Some("Submit answers? [1]/[2]/[q]uit: "))?;
     match answer.as_str() {
         "1" => {
-            if aoc_lib::submit_part_1!(&client, args.year, args.day) {
+            if aoc_lib::submit_part_1!(&client, year, day) {
                 Ok(Action::Continue)
             } else {
                 Ok(Action::Prompt)
             }
         }
         "2" => {
-            if aoc_lib::submit_part_2!(&client, args.year, args.day) {
+            if aoc_lib::submit_part_2!(&client, year, day) {
                 Ok(Action::Quit)
             } else {
                 Ok(Action::Prompt)
@@ -91,6 +98,14 @@ async fn run_loop(client: &Client, args: &Args, running: &Arc<AtomicBool>) -> Re
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
+    if let Some(command) = &args.command {
+        return match command {
+            AocCommand::Test(test_args) => aoc_lib::fixtures::run(test_args),
+            AocCommand::Run(run_args) => aoc_lib::run::run(run_args),
+            AocCommand::Report(report_args) => aoc_lib::report::run(report_args),
+        };
+    }
+
     let client = aoc_lib::aoc::get_client()?;
 
     let running = Arc::new(AtomicBool::new(true));
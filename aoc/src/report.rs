@@ -0,0 +1,262 @@
+use std::fs;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+
+use crate::answers::Answers;
+use crate::aoc;
+use crate::args::ReportArgs;
+use crate::run::{format_duration, list_years};
+use crate::timings::Timings;
+
+/// The section of the README that `aoc report --readme` rewrites
+pub const README_START: &str = "<!-- aoc-report:start -->";
+pub const README_END: &str = "<!-- aoc-report:end -->";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, strum_macros::EnumString)]
+pub enum ReportFormat {
+    #[strum(serialize = "markdown", serialize = "md")]
+    Markdown,
+    #[strum(serialize = "html")]
+    Html,
+    #[strum(serialize = "csv")]
+    Csv,
+}
+
+/// Everything that's known locally about a single day
+#[derive(Debug, Clone)]
+pub struct DayReport {
+    pub year: usize,
+    pub day: usize,
+    pub title: String,
+    pub stars: usize,
+    /// How long after the puzzle unlocked each part was solved
+    pub solved_after: [Option<Duration>; 2],
+    pub run_time: Option<Duration>,
+}
+
+impl DayReport {
+    pub fn source(&self) -> String {
+        format!(
+            "{year}/examples/{year}-{day:02}.rs",
+            year = self.year,
+            day = self.day
+        )
+    }
+}
+
+/// Days since the unix epoch of a date in the proleptic gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Puzzles unlock at midnight EST (UTC-5)
+pub fn unlocked_at(year: usize, day: usize) -> u64 {
+    (days_from_civil(year as i64, 12, day as i64) * 86400 + 5 * 3600) as u64
+}
+
+fn format_solve_time(duration: Option<Duration>) -> String {
+    let minutes = match duration {
+        Some(duration) => duration.as_secs() / 60,
+        None => return String::new(),
+    };
+
+    match (minutes / (60 * 24), minutes / 60 % 24, minutes % 60) {
+        (0, 0, m) => format!("{}m", m),
+        (0, h, m) => format!("{}h {}m", h, m),
+        (d, h, _) => format!("{}d {}h", d, h),
+    }
+}
+
+fn stars(n: usize) -> String {
+    "★".repeat(n)
+}
+
+pub fn collect(year: usize) -> Result<Vec<DayReport>> {
+    let answers = Answers::load(year)?;
+    let timings = Timings::load(year)?;
+
+    aoc::list_days(year)?
+        .into_iter()
+        .map(|day| {
+            let parts = [1, 2].map(|part| answers.get(day, part));
+            let solved_after = parts.map(|answer| {
+                let solved_at = answer?.solved_at?;
+                Some(Duration::from_secs(
+                    solved_at.saturating_sub(unlocked_at(year, day)),
+                ))
+            });
+
+            Ok(DayReport {
+                year,
+                day,
+                title: aoc::get_title(year, day)?.unwrap_or_default(),
+                stars: parts.iter().filter(|answer| answer.is_some()).count(),
+                solved_after,
+                run_time: timings.get(day),
+            })
+        })
+        .collect()
+}
+
+fn summary(days: &[DayReport]) -> String {
+    let stars = days.iter().map(|d| d.stars).sum::<usize>();
+    let run_time = days.iter().filter_map(|d| d.run_time).sum::<Duration>();
+    format!(
+        "{} of {} stars, {} total run time",
+        stars,
+        days.len() * 2,
+        format_duration(run_time)
+    )
+}
+
+pub fn markdown(year: usize, days: &[DayReport]) -> String {
+    let mut out = format!("### {}\n\n{}\n\n", year, summary(days));
+    out.push_str("| Day | Puzzle | Stars | Part 1 solved | Part 2 solved | Run time |\n");
+    out.push_str("| --: | :-- | :-- | --: | --: | --: |\n");
+    for day in days {
+        out.push_str(&format!(
+            "| {:02} | [{}]({}) | {} | {} | {} | {} |\n",
+            day.day,
+            day.title,
+            day.source(),
+            stars(day.stars),
+            format_solve_time(day.solved_after[0]),
+            format_solve_time(day.solved_after[1]),
+            day.run_time.map(format_duration).unwrap_or_default()
+        ));
+    }
+
+    out
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub fn html(year: usize, days: &[DayReport]) -> String {
+    let mut out = format!("<h3>{}</h3>\n<p>{}</p>\n<table>\n", year, summary(days));
+    out.push_str("  <tr><th>Day</th><th>Puzzle</th><th>Stars</th><th>Part 1 solved</th><th>Part 2 solved</th><th>Run time</th></tr>\n");
+    for day in days {
+        out.push_str(&format!(
+            "  <tr><td>{:02}</td><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            day.day,
+            escape_html(&day.source()),
+            escape_html(&day.title),
+            stars(day.stars),
+            format_solve_time(day.solved_after[0]),
+            format_solve_time(day.solved_after[1]),
+            day.run_time.map(format_duration).unwrap_or_default()
+        ));
+    }
+    out.push_str("</table>\n");
+
+    out
+}
+
+fn escape_csv(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+pub fn csv(days: &[DayReport]) -> String {
+    let mut out = String::from(
+        "year,day,title,stars,part_1_solved_after_secs,part_2_solved_after_secs,run_time_ms,source\n",
+    );
+    for day in days {
+        let secs = |d: Option<Duration>| d.map(|d| d.as_secs().to_string()).unwrap_or_default();
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{},{}\n",
+            day.year,
+            day.day,
+            escape_csv(&day.title),
+            day.stars,
+            secs(day.solved_after[0]),
+            secs(day.solved_after[1]),
+            day.run_time
+                .map(|d| format!("{:.3}", d.as_secs_f64() * 1000.0))
+                .unwrap_or_default(),
+            escape_csv(&day.source())
+        ));
+    }
+
+    out
+}
+
+pub fn render(years: &[usize], format: ReportFormat) -> Result<String> {
+    let mut reports = vec![];
+    for year in years {
+        reports.push((*year, collect(*year)?));
+    }
+
+    Ok(match format {
+        ReportFormat::Markdown => reports
+            .iter()
+            .map(|(year, days)| markdown(*year, days))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        ReportFormat::Html => reports
+            .iter()
+            .map(|(year, days)| html(*year, days))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        ReportFormat::Csv => csv(&reports
+            .into_iter()
+            .flat_map(|(_, days)| days)
+            .collect::<Vec<_>>()),
+    })
+}
+
+/// Replace everything between the report markers in `readme` with `report`
+pub fn replace_section(readme: &str, report: &str) -> Result<String> {
+    let start = readme
+        .find(README_START)
+        .ok_or_else(|| anyhow!("the README has no '{}' marker", README_START))?
+        + README_START.len();
+    let end = readme[start..]
+        .find(README_END)
+        .ok_or_else(|| anyhow!("the README has no '{}' marker", README_END))?
+        + start;
+
+    Ok(format!(
+        "{}\n\n{}\n{}",
+        &readme[..start],
+        report.trim_end(),
+        &readme[end..]
+    ))
+}
+
+/// `aoc report`: summarise the progress of each year from the local solutions, answers and timings
+pub fn run(args: &ReportArgs) -> Result<()> {
+    let years = match args.year {
+        Some(year) => vec![year],
+        None => list_years()?,
+    };
+
+    if args.readme {
+        let report = render(&years, ReportFormat::Markdown)?;
+        let readme = fs::read_to_string("README.md")?;
+        fs::write("README.md", replace_section(&readme, &report)?)?;
+        println!("Updated the report in README.md");
+        return Ok(());
+    }
+
+    let report = render(&years, args.format)?;
+    match &args.output {
+        Some(path) => fs::write(path, report)?,
+        None => print!("{}", report),
+    }
+
+    Ok(())
+}
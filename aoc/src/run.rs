@@ -0,0 +1,356 @@
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+
+use crate::answers::{Answer, Answers};
+use crate::aoc;
+use crate::args::RunArgs;
+use crate::timings::Timings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Success,
+    Failed,
+    TimedOut,
+}
+
+/// The outcome of running a single day's solution
+#[derive(Debug, Clone)]
+pub struct DayResult {
+    pub year: usize,
+    pub day: usize,
+    pub title: String,
+    pub part_1: Option<String>,
+    pub part_2: Option<String>,
+    pub elapsed: Duration,
+    pub status: Status,
+    /// Why the solution couldn't be run at all, e.g. a missing binary
+    pub error: Option<String>,
+}
+
+impl DayResult {
+    pub fn part(&self, part: usize) -> Option<&String> {
+        match part {
+            1 => self.part_1.as_ref(),
+            _ => self.part_2.as_ref(),
+        }
+    }
+}
+
+/// How an answer compares with the recorded one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Check {
+    Correct,
+    Incorrect,
+    Unknown,
+    Missing,
+}
+
+impl Check {
+    pub fn new(answer: Option<&String>, expected: Option<&Answer>) -> Check {
+        match (answer, expected) {
+            (Some(answer), Some(expected)) if *answer == expected.answer => Check::Correct,
+            (_, Some(_)) => Check::Incorrect,
+            (Some(_), None) => Check::Unknown,
+            (None, None) => Check::Missing,
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Check::Correct => "✓",
+            Check::Incorrect => "✗",
+            Check::Unknown => "?",
+            Check::Missing => "-",
+        }
+    }
+}
+
+/// All the years in the workspace that have solutions
+pub fn list_years() -> Result<Vec<usize>> {
+    let mut years = fs::read_dir(".")?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().join("examples").is_dir())
+        .filter_map(|entry| entry.file_name().to_string_lossy().parse().ok())
+        .collect::<Vec<usize>>();
+    years.sort_unstable();
+
+    Ok(years)
+}
+
+fn target_dir(release: bool) -> PathBuf {
+    PathBuf::from(env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| String::from("target")))
+        .join(if release { "release" } else { "debug" })
+        .join("examples")
+}
+
+fn build_examples(year: usize, release: bool) -> Result<()> {
+    let package = format!("_{}", year);
+    let mut cargo_args = vec!["build", "-p", &package, "--examples"];
+    if release {
+        cargo_args.push("--release");
+    }
+
+    let status = Command::new("cargo")
+        .args(&cargo_args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(anyhow!("failed to build the solutions for {}", year))
+    }
+}
+
+fn read_to_end(mut reader: impl Read + Send + 'static) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut buf = vec![];
+        let _ = reader.read_to_end(&mut buf);
+        String::from_utf8_lossy(&buf).to_string()
+    })
+}
+
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> Result<Status> {
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(if status.success() {
+                Status::Success
+            } else {
+                Status::Failed
+            });
+        }
+
+        if start.elapsed() > timeout {
+            child.kill()?;
+            child.wait()?;
+            return Ok(Status::TimedOut);
+        }
+
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+/// Run a single (already built) solution and collect the answers it printed
+pub fn run_day(year: usize, day: usize, release: bool, timeout: Duration) -> Result<DayResult> {
+    let binary = target_dir(release).join(format!("{}-{:02}", year, day));
+
+    let start = Instant::now();
+    let mut child = Command::new(&binary)
+        // don't replace the answers of the day that's being worked on
+        .env("AOC_NO_SAVE", "1")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| anyhow!("failed to run {}: {}", binary.display(), e))?;
+
+    let stdout = read_to_end(child.stdout.take().unwrap());
+    let status = wait_with_timeout(&mut child, timeout)?;
+    let elapsed = start.elapsed();
+    let stdout = stdout.join().unwrap_or_default();
+
+    // the last value a solution set for a part is its answer
+    let answer = |part: &str| {
+        let prefix = format!("{}: ", part);
+        stdout
+            .lines()
+            .rev()
+            .find_map(|line| line.strip_prefix(&prefix))
+            .map(|s| s.to_string())
+    };
+
+    Ok(DayResult {
+        year,
+        day,
+        title: aoc::get_title(year, day)?.unwrap_or_default(),
+        part_1: answer("part_1"),
+        part_2: answer("part_2"),
+        elapsed,
+        status,
+        error: None,
+    })
+}
+
+/// Run every job across all cores. A day that can't be run is reported as failed, like one that
+/// times out, so it doesn't hide the results of the other days.
+fn run_all(jobs: &[(usize, usize)], release: bool, timeout: Duration) -> Vec<DayResult> {
+    let workers = thread::available_parallelism().map_or(1, |n| n.get());
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(vec![]);
+
+    thread::scope(|scope| {
+        for _ in 0..workers.min(jobs.len()) {
+            scope.spawn(|| {
+                while let Some((year, day)) = jobs.get(next.fetch_add(1, Ordering::SeqCst)) {
+                    let start = Instant::now();
+                    let result =
+                        run_day(*year, *day, release, timeout).unwrap_or_else(|e| DayResult {
+                            year: *year,
+                            day: *day,
+                            title: String::new(),
+                            part_1: None,
+                            part_2: None,
+                            elapsed: start.elapsed(),
+                            status: Status::Failed,
+                            error: Some(e.to_string()),
+                        });
+                    results.lock().unwrap().push(result);
+                }
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|r| (r.year, r.day));
+
+    results
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs_f64();
+    if secs >= 1.0 {
+        format!("{:.2}s", secs)
+    } else {
+        format!("{:.1}ms", secs * 1000.0)
+    }
+}
+
+fn print_table(year: usize, results: &[&DayResult], answers: &Answers) {
+    let mut rows = vec![vec![
+        String::from("Day"),
+        String::from("Title"),
+        String::from("Part 1"),
+        String::from("Part 2"),
+        String::from("Check"),
+        String::from("Time"),
+    ]];
+
+    let (mut correct, mut recorded, mut total_time) = (0, 0, Duration::default());
+    for result in results {
+        let checks =
+            [1, 2].map(|part| Check::new(result.part(part), answers.get(result.day, part)));
+        correct += checks.iter().filter(|c| **c == Check::Correct).count();
+        recorded += [1, 2]
+            .iter()
+            .filter(|part| answers.get(result.day, **part).is_some())
+            .count();
+        total_time += result.elapsed;
+
+        rows.push(vec![
+            format!("{:02}", result.day),
+            result.title.clone(),
+            result.part_1.clone().unwrap_or_default(),
+            result.part_2.clone().unwrap_or_default(),
+            match result.status {
+                Status::Success => format!("{} {}", checks[0].symbol(), checks[1].symbol()),
+                Status::Failed => String::from("failed"),
+                Status::TimedOut => String::from("timeout"),
+            },
+            format_duration(result.elapsed),
+        ]);
+    }
+
+    rows.push(vec![
+        String::from("Total"),
+        format!("{} days", results.len()),
+        String::new(),
+        String::new(),
+        format!("{}/{}", correct, recorded),
+        format_duration(total_time),
+    ]);
+
+    let widths = (0..rows[0].len())
+        .map(|i| rows.iter().map(|row| row[i].chars().count()).max().unwrap())
+        .collect::<Vec<_>>();
+
+    println!("\n{}", year);
+    for (i, row) in rows.iter().enumerate() {
+        if i == 1 || i == rows.len() - 1 {
+            let line = widths.iter().map(|w| "-".repeat(*w)).collect::<Vec<_>>();
+            println!("{}", line.join("  "));
+        }
+
+        let cells = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{}{}", cell, " ".repeat(width - cell.chars().count())))
+            .collect::<Vec<_>>();
+        println!("{}", cells.join("  ").trim_end());
+    }
+
+    for result in results {
+        if let Some(error) = &result.error {
+            println!("Day {:02}: {}", result.day, error);
+        }
+    }
+}
+
+/// `aoc run`: run every solution of a year (or all years) in parallel and summarise the results
+pub fn run(args: &RunArgs) -> Result<()> {
+    let years = match args.year {
+        Some(year) if !args.all => vec![year],
+        _ => list_years()?,
+    };
+
+    let mut jobs = vec![];
+    for year in &years {
+        build_examples(*year, args.release)?;
+        jobs.extend(aoc::list_days(*year)?.into_iter().map(|day| (*year, day)));
+    }
+
+    let start = Instant::now();
+    let results = run_all(&jobs, args.release, Duration::from_secs(args.timeout));
+    let wall_time = start.elapsed();
+
+    for year in years {
+        let mut answers = Answers::load(year)?;
+        let year_results = results
+            .iter()
+            .filter(|r| r.year == year)
+            .collect::<Vec<_>>();
+        print_table(year, &year_results, &answers);
+
+        if args.record {
+            let mut timings = Timings::load(year)?;
+            for result in year_results.iter().filter(|r| r.status == Status::Success) {
+                timings.insert(result.day, result.elapsed);
+                for part in [1, 2] {
+                    if let (Some(answer), None) = (result.part(part), answers.get(result.day, part))
+                    {
+                        let answer = Answer {
+                            answer: answer.clone(),
+                            solved_at: None,
+                        };
+                        answers.insert(result.day, part, answer);
+                    }
+                }
+            }
+
+            if !answers.is_empty() {
+                answers.save()?;
+            }
+            timings.save()?;
+        }
+    }
+
+    println!(
+        "\nRan {} days in {} ({} across all days)",
+        results.len(),
+        format_duration(wall_time),
+        format_duration(results.iter().map(|r| r.elapsed).sum())
+    );
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+
+/// The recorded run times for a year, stored in `{year}/timings.txt` with one line per day:
+///
+/// ```text
+/// <day> <microseconds>
+/// ```
+#[derive(Debug, Clone)]
+pub struct Timings {
+    year: usize,
+    entries: BTreeMap<usize, Duration>,
+}
+
+impl Timings {
+    pub fn path(year: usize) -> PathBuf {
+        PathBuf::from(format!("{}/timings.txt", year))
+    }
+
+    pub fn load(year: usize) -> Result<Timings> {
+        let mut timings = Timings {
+            year,
+            entries: BTreeMap::new(),
+        };
+
+        let contents = match fs::read_to_string(Timings::path(year)) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(timings),
+            Err(e) => return Err(e.into()),
+        };
+
+        for line in contents.lines().filter(|l| !l.trim().is_empty()) {
+            let (day, micros) = line
+                .split_once(' ')
+                .ok_or_else(|| anyhow!("invalid timing line: '{}'", line))?;
+            timings
+                .entries
+                .insert(day.parse()?, Duration::from_micros(micros.trim().parse()?));
+        }
+
+        Ok(timings)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let mut contents = String::new();
+        for (day, elapsed) in &self.entries {
+            contents.push_str(&format!("{:02} {}\n", day, elapsed.as_micros()));
+        }
+
+        Ok(fs::write(Timings::path(self.year), contents)?)
+    }
+
+    pub fn get(&self, day: usize) -> Option<Duration> {
+        self.entries.get(&day).copied()
+    }
+
+    pub fn insert(&mut self, day: usize, elapsed: Duration) {
+        self.entries.insert(day, elapsed);
+    }
+}
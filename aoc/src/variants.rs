@@ -0,0 +1,68 @@
+use std::fmt::Debug;
+use std::time::{Duration, Instant};
+
+/// Run every variant, print how long each one took and make sure they all produced the same answer.
+/// Panics if any of the variants disagree, otherwise returns the answer of the first variant.
+pub fn cross_check<T>(variants: &[(&str, &dyn Fn() -> T)]) -> T
+where
+    T: PartialEq + Debug,
+{
+    assert!(!variants.is_empty(), "no variants were given");
+
+    let mut results = variants
+        .iter()
+        .map(|(name, f)| {
+            let start = Instant::now();
+            let value = f();
+            (*name, value, start.elapsed())
+        })
+        .collect::<Vec<(&str, T, Duration)>>();
+
+    let name_width = results.iter().map(|(name, ..)| name.len()).max().unwrap();
+    let fastest = results.iter().map(|(.., elapsed)| *elapsed).min().unwrap();
+    for (name, _, elapsed) in &results {
+        println!(
+            "variant {:<width$}  {:>12?}  x{:.2}",
+            name,
+            elapsed,
+            elapsed.as_secs_f64() / fastest.as_secs_f64().max(f64::EPSILON),
+            width = name_width
+        );
+    }
+
+    let (first_name, first_value, _) = &results[0];
+    let mismatches = results[1..]
+        .iter()
+        .filter(|(_, value, _)| value != first_value)
+        .map(|(name, value, _)| format!("{} = {:?}", name, value))
+        .collect::<Vec<_>>();
+    if !mismatches.is_empty() {
+        panic!(
+            "variants disagree: {} = {:?}, {}",
+            first_name,
+            first_value,
+            mismatches.join(", ")
+        );
+    }
+
+    results.swap_remove(0).1
+}
+
+/// Declare several named implementations of the same answer, e.g.:
+///
+/// ```ignore
+/// aoc_lib::set_part_1!(aoc_lib::variants! {
+///     "readable" => solve_slowly(&input),
+///     "fast" => solve_quickly(&input),
+/// });
+/// ```
+///
+/// Each variant is run and timed, and they're all checked to agree (see `variants::cross_check`).
+#[macro_export]
+macro_rules! variants {
+    ($($name:expr => $body:expr),+ $(,)?) => {{
+        aoc_lib::variants::cross_check(&[
+            $(($name, &(|| $body) as &dyn Fn() -> _)),+
+        ])
+    }};
+}